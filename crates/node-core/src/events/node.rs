@@ -25,7 +25,7 @@ use std::{
     time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 use tokio::time::Interval;
-use tracing::{debug, info, warn};
+use tracing::{debug, error, info, warn};
 
 /// Interval of reporting node state.
 const INFO_MESSAGE_INTERVAL: Duration = Duration::from_secs(25);
@@ -324,8 +324,34 @@ impl<DB> NodeState<DB> {
 
     fn handle_static_file_producer_event(&self, event: StaticFileProducerEvent) {
         match event {
-            StaticFileProducerEvent::Started { targets } => {
-                info!(?targets, "Static File Producer started");
+            StaticFileProducerEvent::Started { targets, tip } => {
+                info!(?targets, ?tip, "Static File Producer started");
+            }
+            StaticFileProducerEvent::DryRun { targets, estimate } => {
+                info!(?targets, ?estimate, "Static File Producer dry run");
+            }
+            StaticFileProducerEvent::Checkpoint { segment, block_number } => {
+                debug!(%segment, block_number, "Static File Producer checkpoint");
+            }
+            StaticFileProducerEvent::Progress { segment, processed, total } => {
+                let progress =
+                    OptionalField(EntitiesCheckpoint { processed, total }.fmt_percentage());
+                info!(%segment, %progress, "Static File Producer progress");
+            }
+            StaticFileProducerEvent::SegmentFinished { segment, block_range, files } => {
+                debug!(%segment, ?block_range, ?files, "Static File Producer segment finished");
+            }
+            StaticFileProducerEvent::SegmentError { segment, error } => {
+                error!(%segment, %error, "Static File Producer segment failed");
+            }
+            StaticFileProducerEvent::VerificationFailed { segment, block_range, reason } => {
+                warn!(%segment, ?block_range, ?reason, "Static File verification failed");
+            }
+            StaticFileProducerEvent::Aborted { targets, elapsed } => {
+                warn!(?targets, ?elapsed, "Static File Producer aborted");
+            }
+            StaticFileProducerEvent::Failed { segment, error, elapsed } => {
+                error!(%segment, %error, ?elapsed, "Static File Producer failed");
             }
             StaticFileProducerEvent::Finished { targets, elapsed } => {
                 info!(?targets, ?elapsed, "Static File Producer finished");
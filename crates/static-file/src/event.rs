@@ -1,5 +1,7 @@
-use crate::StaticFileTargets;
-use std::time::Duration;
+use crate::{StaticFileEstimate, StaticFileTargets};
+use reth_interfaces::RethError;
+use reth_primitives::{BlockNumber, StaticFileSegment, TxNumber, B256};
+use std::{ops::RangeInclusive, path::PathBuf, time::Duration};
 
 /// An event emitted by a [StaticFileProducer][crate::StaticFileProducer].
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -8,6 +10,78 @@ pub enum StaticFileProducerEvent {
     Started {
         /// Targets that will be moved to static files
         targets: StaticFileTargets,
+        /// Highest block visible to the read transaction shared by all segments. Set only if
+        /// consistent reads are enabled.
+        tip: Option<BlockNumber>,
+    },
+    /// Emitted when static file producer validated the targets without moving any data.
+    DryRun {
+        /// Targets that would be moved to static files
+        targets: StaticFileTargets,
+        /// Estimated amount of data that would be moved to static files
+        estimate: StaticFileEstimate,
+    },
+    /// Emitted when a segment has committed its progress to static files. Only emitted if the
+    /// checkpoint interval is set.
+    Checkpoint {
+        /// Segment that was committed
+        segment: StaticFileSegment,
+        /// Highest block number of the segment that was moved to static files
+        block_number: BlockNumber,
+    },
+    /// Emitted periodically while a segment is being moved to static files. Only emitted if the
+    /// progress interval is set.
+    Progress {
+        /// Segment that is being moved
+        segment: StaticFileSegment,
+        /// Number of blocks that were already moved
+        processed: u64,
+        /// Total number of blocks to move
+        total: u64,
+    },
+    /// Emitted when a segment was moved to static files and all its files were committed.
+    SegmentFinished {
+        /// Segment that was moved
+        segment: StaticFileSegment,
+        /// Block range that was moved
+        block_range: RangeInclusive<BlockNumber>,
+        /// Paths of all files of the static files containing the block range
+        files: Vec<PathBuf>,
+    },
+    /// Emitted when a segment failed to be moved to static files. Only emitted if the static file
+    /// producer continues on error, otherwise [StaticFileProducerEvent::Failed] is emitted.
+    SegmentError {
+        /// Segment that failed
+        segment: StaticFileSegment,
+        /// Error that the segment failed with
+        error: RethError,
+    },
+    /// Emitted when a static file failed the verification.
+    VerificationFailed {
+        /// Segment of the static file
+        segment: StaticFileSegment,
+        /// Targeted block range that is covered by the static file
+        block_range: RangeInclusive<BlockNumber>,
+        /// Reason of the failure
+        reason: StaticFileVerificationFailure,
+    },
+    /// Emitted when static file producer was aborted before moving all targets.
+    Aborted {
+        /// Targets that were moved to static files before the abort
+        targets: StaticFileTargets,
+        /// Time it took to run the static file producer
+        elapsed: Duration,
+    },
+    /// Emitted when static file producer failed, because a segment failed to be moved to static
+    /// files and the static file producer doesn't continue on error. Nothing is committed except
+    /// for the checkpoints that the segments have reached.
+    Failed {
+        /// Segment that failed
+        segment: StaticFileSegment,
+        /// Error that the segment failed with
+        error: RethError,
+        /// Time it took to run the static file producer
+        elapsed: Duration,
     },
     /// Emitted when static file producer finished running.
     Finished {
@@ -17,3 +91,54 @@ pub enum StaticFileProducerEvent {
         elapsed: Duration,
     },
 }
+
+/// Reason of a failed static file verification.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum StaticFileVerificationFailure {
+    /// Static file doesn't exist.
+    MissingFile,
+    /// Static file doesn't cover all targeted blocks.
+    BlockRangeMismatch {
+        /// Block range of the static file
+        got: Option<RangeInclusive<BlockNumber>>,
+    },
+    /// Transaction range of the static file doesn't match the transactions of its blocks in the
+    /// database.
+    TxRangeMismatch {
+        /// Transaction range of the blocks according to the database
+        expected: Option<RangeInclusive<TxNumber>>,
+        /// Transaction range of the static file
+        got: Option<RangeInclusive<TxNumber>>,
+    },
+    /// Number of rows doesn't match the blocks or transactions of the static file in the
+    /// database.
+    RowCountMismatch {
+        /// Number of rows according to the database
+        expected: usize,
+        /// Number of rows in the static file
+        got: usize,
+    },
+    /// Number of offsets doesn't match the number of rows and columns.
+    OffsetsCountMismatch {
+        /// Number of offsets according to the number of rows and columns
+        expected: usize,
+        /// Number of offsets in the offsets file
+        got: usize,
+    },
+    /// Size of the data file doesn't match the last offset.
+    DataSizeMismatch {
+        /// Size of the data file according to the last offset
+        expected: usize,
+        /// Size of the data file
+        got: usize,
+    },
+    /// Content hash of a file doesn't match the hash in the manifest.
+    HashMismatch {
+        /// File name
+        file: String,
+        /// Hash of the file according to the manifest
+        expected: B256,
+        /// Hash of the file, or [None] if it doesn't exist
+        got: Option<B256>,
+    },
+}
@@ -8,11 +8,13 @@
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 
 mod event;
+mod manifest;
 pub mod segments;
 mod static_file_producer;
 
-pub use event::StaticFileProducerEvent;
+pub use event::{StaticFileProducerEvent, StaticFileVerificationFailure};
+pub use manifest::{StaticFileManifest, StaticFileManifestEntry};
 pub use static_file_producer::{
-    StaticFileProducer, StaticFileProducerInner, StaticFileProducerResult,
-    StaticFileProducerWithResult, StaticFileTargets,
+    StaticFileEstimate, StaticFileProducer, StaticFileProducerAbortHandle, StaticFileProducerInner,
+    StaticFileProducerResult, StaticFileProducerWithResult, StaticFileTargets,
 };
@@ -0,0 +1,103 @@
+//! Manifests of static files with content hashes of their files.
+
+use crate::static_file_producer::static_file_paths;
+use reth_interfaces::{RethError, RethResult};
+use reth_nippy_jar::NippyJar;
+use reth_primitives::{
+    fs::{self, FsPathError},
+    static_file::SegmentHeader,
+    BlockNumber, StaticFileSegment, B256,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    io::Read,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+
+/// Extension of the manifest file of a static file.
+const MANIFEST_FILE_EXTENSION: &str = "manifest";
+
+/// Size of the buffer used to read files when hashing them.
+const HASH_BUFFER_SIZE: usize = 1024 * 1024;
+
+/// Manifest of a static file, listing all its files with their content hashes and the block range
+/// that it covers. Allows to verify the integrity of distributed static files.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StaticFileManifest {
+    /// Segment of the static file
+    pub segment: StaticFileSegment,
+    /// Block range covered by the static file
+    pub block_range: RangeInclusive<BlockNumber>,
+    /// Files of the static file
+    pub files: Vec<StaticFileManifestEntry>,
+}
+
+/// File of a static file listed in a [StaticFileManifest].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StaticFileManifestEntry {
+    /// File name, relative to the directory of the manifest
+    pub name: String,
+    /// SHA-256 hash of the file contents
+    pub hash: B256,
+}
+
+impl StaticFileManifest {
+    /// Creates a manifest of the static file by hashing all its files.
+    pub fn new(jar: &NippyJar<SegmentHeader>) -> RethResult<Self> {
+        let block_range = jar.user_header().block_range().ok_or_else(|| {
+            RethError::Custom(format!("static file {} has no blocks", jar.data_path().display()))
+        })?;
+
+        let files = static_file_paths(jar)
+            .into_iter()
+            .map(|path| {
+                let name = path
+                    .file_name()
+                    .expect("static file path has a file name")
+                    .to_string_lossy()
+                    .into_owned();
+                Ok(StaticFileManifestEntry { name, hash: hash_file(&path)? })
+            })
+            .collect::<RethResult<Vec<_>>>()?;
+
+        Ok(Self { segment: jar.user_header().segment(), block_range: block_range.into(), files })
+    }
+
+    /// Returns the path of the manifest of a static file with the provided data file path.
+    pub fn path(data_path: &Path) -> PathBuf {
+        data_path.with_extension(MANIFEST_FILE_EXTENSION)
+    }
+
+    /// Loads a manifest from the provided path.
+    pub fn load(path: &Path) -> RethResult<Self> {
+        serde_json::from_slice(&fs::read(path)?).map_err(|err| {
+            RethError::Custom(format!("failed to decode manifest {}: {err}", path.display()))
+        })
+    }
+
+    /// Writes the manifest to the provided path.
+    pub fn write(&self, path: &Path) -> RethResult<()> {
+        let manifest = serde_json::to_vec_pretty(self)
+            .map_err(|err| RethError::Custom(format!("failed to encode manifest: {err}")))?;
+        Ok(fs::write(path, manifest)?)
+    }
+}
+
+/// Returns the SHA-256 hash of the file contents.
+pub(crate) fn hash_file(path: &Path) -> Result<B256, FsPathError> {
+    let mut file = std::fs::File::open(path).map_err(|err| FsPathError::open(err, path))?;
+
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0; HASH_BUFFER_SIZE];
+    loop {
+        let read = file.read(&mut buf).map_err(|err| FsPathError::read(err, path))?;
+        if read == 0 {
+            break
+        }
+        hasher.update(&buf[..read]);
+    }
+
+    Ok(B256::from_slice(&hasher.finalize()))
+}
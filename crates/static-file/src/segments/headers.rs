@@ -1,9 +1,12 @@
-use crate::segments::{dataset_for_compression, prepare_jar, Segment, SegmentHeader};
+use crate::segments::{
+    dataset_for_compression, estimate_from_samples, prepare_jar, Rows, Segment, SegmentEstimate,
+    SegmentHeader,
+};
 use reth_db::{
     cursor::DbCursorRO, database::Database, static_file::create_static_file_T1_T2_T3, tables,
     transaction::DbTx, RawKey, RawTable,
 };
-use reth_interfaces::provider::ProviderResult;
+use reth_interfaces::provider::{ProviderError, ProviderResult};
 use reth_primitives::{static_file::SegmentConfig, BlockNumber, StaticFileSegment};
 use reth_provider::{
     providers::{StaticFileProvider, StaticFileWriter},
@@ -22,7 +25,7 @@ impl<DB: Database> Segment<DB> for Headers {
 
     fn copy_to_static_files(
         &self,
-        provider: DatabaseProviderRO<DB>,
+        provider: &DatabaseProviderRO<DB>,
         static_file_provider: StaticFileProvider,
         block_range: RangeInclusive<BlockNumber>,
     ) -> ProviderResult<()> {
@@ -58,6 +61,39 @@ impl<DB: Database> Segment<DB> for Headers {
         Ok(())
     }
 
+    fn estimate(
+        &self,
+        provider: &DatabaseProviderRO<DB>,
+        block_range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<SegmentEstimate> {
+        let range_len = block_range.clone().count();
+
+        let mut samples = [
+            dataset_for_compression::<DB, tables::Headers>(provider, &block_range, range_len)?,
+            dataset_for_compression::<DB, tables::HeaderTerminalDifficulties>(
+                provider,
+                &block_range,
+                range_len,
+            )?,
+            dataset_for_compression::<DB, tables::CanonicalHeaders>(
+                provider,
+                &block_range,
+                range_len,
+            )?,
+        ];
+        // Headers are written to static files directly by the pipeline, so the database usually
+        // has no rows to sample from
+        if samples.iter().all(Vec::is_empty) {
+            samples = dataset_from_static_files(
+                provider.static_file_provider(),
+                &block_range,
+                range_len,
+            )?;
+        }
+
+        Ok(estimate_from_samples(range_len as u64, range_len as u64, samples))
+    }
+
     fn create_static_file_file(
         &self,
         provider: &DatabaseProviderRO<DB>,
@@ -127,3 +163,46 @@ impl<DB: Database> Segment<DB> for Headers {
         Ok(())
     }
 }
+
+/// Generates the dataset with the most recent rows (at most 1000) of the headers static file that
+/// contains the end of the range, or the highest one if the range is past it.
+fn dataset_from_static_files(
+    static_file_provider: &StaticFileProvider,
+    range: &RangeInclusive<BlockNumber>,
+    range_len: usize,
+) -> ProviderResult<Rows<3>> {
+    let mut dataset: Rows<3> = Default::default();
+
+    let Some(end) = static_file_provider
+        .get_highest_static_file_block(StaticFileSegment::Headers)
+        .map(|highest| highest.min(*range.end()))
+    else {
+        return Ok(dataset)
+    };
+
+    let jar_provider = static_file_provider.get_segment_provider_from_block(
+        StaticFileSegment::Headers,
+        end,
+        None,
+    )?;
+    let Some(block_start) = jar_provider.user_header().block_start().filter(|start| *start <= end)
+    else {
+        return Ok(dataset)
+    };
+
+    let mut cursor = jar_provider.cursor()?;
+    for block in (block_start..=end).rev().take(range_len.min(1000)) {
+        let Some(row) = cursor
+            .row_by_number((block - block_start) as usize)
+            .map_err(|err| ProviderError::NippyJar(err.to_string()))?
+        else {
+            break
+        };
+
+        for (column, value) in dataset.iter_mut().zip(row) {
+            column.push(value.to_vec());
+        }
+    }
+
+    Ok(dataset)
+}
@@ -28,6 +28,20 @@ use std::{ops::RangeInclusive, path::Path};
 
 pub(crate) type Rows<const COLUMNS: usize> = [Vec<Vec<u8>>; COLUMNS];
 
+/// Size of one offset in the offsets file of a static file.
+const OFFSET_SIZE_BYTES: u64 = 8;
+
+/// Estimated amount of data that a [Segment] would move to static files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SegmentEstimate {
+    /// Number of blocks.
+    pub blocks: u64,
+    /// Number of rows.
+    pub rows: u64,
+    /// Approximate size of the data and offsets files in bytes, before compression.
+    pub bytes: u64,
+}
+
 /// A segment represents moving some portion of the data to static files.
 pub trait Segment<DB: Database>: Send + Sync {
     /// Returns the [`StaticFileSegment`].
@@ -37,11 +51,19 @@ pub trait Segment<DB: Database>: Send + Sync {
     /// management of and writing to files.
     fn copy_to_static_files(
         &self,
-        provider: DatabaseProviderRO<DB>,
+        provider: &DatabaseProviderRO<DB>,
         static_file_provider: StaticFileProvider,
         block_range: RangeInclusive<BlockNumber>,
     ) -> ProviderResult<()>;
 
+    /// Estimate the amount of data that would be moved to static files for the provided block
+    /// range, without writing anything.
+    fn estimate(
+        &self,
+        provider: &DatabaseProviderRO<DB>,
+        block_range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<SegmentEstimate>;
+
     /// Create a static file of data for the provided block range. The `directory` parameter
     /// determines the static file's save location.
     fn create_static_file_file(
@@ -118,3 +140,21 @@ pub(crate) fn dataset_for_compression<DB: Database, T: Table<Key = u64>>(
         .map(|row| row.map(|(_key, value)| value.into_value()).expect("should exist"))
         .collect::<Vec<_>>())
 }
+
+/// Estimates the size of `rows` rows using the samples of values for every column, as returned by
+/// [dataset_for_compression].
+pub(crate) fn estimate_from_samples<const COLUMNS: usize>(
+    blocks: u64,
+    rows: u64,
+    samples: Rows<COLUMNS>,
+) -> SegmentEstimate {
+    let row_size = samples
+        .iter()
+        .map(|column| {
+            let column_size = column.iter().map(|value| value.len() as u64).sum::<u64>();
+            column_size.checked_div(column.len() as u64).unwrap_or_default() + OFFSET_SIZE_BYTES
+        })
+        .sum::<u64>();
+
+    SegmentEstimate { blocks, rows, bytes: rows * row_size }
+}
@@ -1,4 +1,6 @@
-use crate::segments::{dataset_for_compression, prepare_jar, Segment};
+use crate::segments::{
+    dataset_for_compression, estimate_from_samples, prepare_jar, Segment, SegmentEstimate,
+};
 use reth_db::{
     cursor::DbCursorRO, database::Database, static_file::create_static_file_T1, tables,
     transaction::DbTx,
@@ -27,7 +29,7 @@ impl<DB: Database> Segment<DB> for Transactions {
     /// [StaticFileSegment::Transactions] for the provided block range.
     fn copy_to_static_files(
         &self,
-        provider: DatabaseProviderRO<DB>,
+        provider: &DatabaseProviderRO<DB>,
         static_file_provider: StaticFileProvider,
         block_range: RangeInclusive<BlockNumber>,
     ) -> ProviderResult<()> {
@@ -58,6 +60,25 @@ impl<DB: Database> Segment<DB> for Transactions {
         Ok(())
     }
 
+    fn estimate(
+        &self,
+        provider: &DatabaseProviderRO<DB>,
+        block_range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<SegmentEstimate> {
+        let tx_range = provider.transaction_range_by_block_range(block_range.clone())?;
+        let tx_range_len = tx_range.clone().count();
+
+        Ok(estimate_from_samples(
+            block_range.count() as u64,
+            tx_range_len as u64,
+            [dataset_for_compression::<DB, tables::Transactions>(
+                provider,
+                &tx_range,
+                tx_range_len,
+            )?],
+        ))
+    }
+
     fn create_static_file_file(
         &self,
         provider: &DatabaseProviderRO<DB>,
@@ -1,19 +1,34 @@
 //! Support for producing static files.
 
-use crate::{segments, segments::Segment, StaticFileProducerEvent};
+use crate::{
+    manifest::hash_file,
+    segments,
+    segments::{Segment, SegmentEstimate},
+    StaticFileManifest, StaticFileProducerEvent, StaticFileVerificationFailure,
+};
 use parking_lot::Mutex;
 use rayon::prelude::*;
-use reth_db::database::Database;
-use reth_interfaces::RethResult;
-use reth_primitives::{static_file::HighestStaticFiles, BlockNumber, PruneModes};
+use reth_db::{database::Database, tables, transaction::DbTx};
+use reth_interfaces::{provider::ProviderError, RethError, RethResult};
+use reth_nippy_jar::{NippyJar, CONFIG_FILE_EXTENSION, OFFSETS_FILE_EXTENSION};
+use reth_primitives::{
+    static_file::{find_fixed_range, HighestStaticFiles, SegmentHeader, SegmentRangeInclusive},
+    BlockNumber, PruneModes, StaticFileSegment,
+};
 use reth_provider::{
     providers::{StaticFileProvider, StaticFileWriter},
-    ProviderFactory,
+    BlockNumReader, BlockReader, ProviderFactory,
 };
 use reth_tokio_util::EventListeners;
 use std::{
+    collections::BTreeMap,
+    ffi::OsStr,
     ops::{Deref, RangeInclusive},
-    sync::Arc,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
     time::Instant,
 };
 use tokio_stream::wrappers::UnboundedReceiverStream;
@@ -22,6 +37,10 @@ use tracing::{debug, trace};
 /// Result of [StaticFileProducerInner::run] execution.
 pub type StaticFileProducerResult = RethResult<StaticFileTargets>;
 
+/// Estimated amount of data per segment that [StaticFileProducerInner::run] would move to static
+/// files. See [StaticFileProducerInner::estimate].
+pub type StaticFileEstimate = BTreeMap<StaticFileSegment, SegmentEstimate>;
+
 /// The [StaticFileProducer] instance itself with the result of [StaticFileProducerInner::run]
 pub type StaticFileProducerWithResult<DB> = (StaticFileProducer<DB>, StaticFileProducerResult);
 
@@ -43,6 +62,51 @@ impl<DB: Database> StaticFileProducer<DB> {
             prune_modes,
         ))))
     }
+
+    /// Sets the number of blocks after which the progress of every segment is committed to the
+    /// static files, so that an interrupted run can be resumed from the last committed block.
+    pub fn with_checkpoint_interval(self, checkpoint_interval: u64) -> Self {
+        self.lock().checkpoint_interval = Some(checkpoint_interval);
+        self
+    }
+
+    /// Makes all segments read from a single read-only database transaction opened at the start
+    /// of the run, so that they see a consistent database state even if new blocks are written
+    /// during the run.
+    pub fn with_consistent_reads(self) -> Self {
+        self.lock().consistent_reads = true;
+        self
+    }
+
+    /// Makes a failing segment not fail the whole run. The error is reported with
+    /// [StaticFileProducerEvent::SegmentError] and the remaining segments are moved as usual.
+    pub fn with_continue_on_error(self) -> Self {
+        self.lock().continue_on_error = true;
+        self
+    }
+
+    /// Makes the run write a [StaticFileManifest] next to every static file it has finalized, i.e.
+    /// filled with all blocks of its fixed block range.
+    pub fn with_manifests(self) -> Self {
+        self.lock().write_manifests = true;
+        self
+    }
+
+    /// Sets the number of blocks after which the progress of every segment is reported with
+    /// [StaticFileProducerEvent::Progress].
+    pub fn with_progress_interval(self, progress_interval: u64) -> Self {
+        self.lock().progress_interval = Some(progress_interval);
+        self
+    }
+
+    /// Sets the maximum number of segments that are moved to static files in parallel. Segments
+    /// are then run on a dedicated thread pool with that many threads instead of the global one.
+    ///
+    /// Returns an error if the maximum is zero or the thread pool can't be built.
+    pub fn with_max_parallel_segments(self, max_parallel_segments: usize) -> RethResult<Self> {
+        self.lock().thread_pool = Some(segments_thread_pool(max_parallel_segments)?);
+        Ok(self)
+    }
 }
 
 impl<DB> Deref for StaticFileProducer<DB> {
@@ -64,11 +128,66 @@ pub struct StaticFileProducerInner<DB> {
     /// needed in [StaticFileProducerInner] to prevent attempting to move prunable data to static
     /// files. See [StaticFileProducerInner::get_static_file_targets].
     prune_modes: PruneModes,
+    /// Number of blocks after which the progress of every segment is committed to the static
+    /// files and reported with [StaticFileProducerEvent::Checkpoint]. If the run is interrupted,
+    /// the next one resumes from the last committed block, because targets are calculated from the
+    /// highest static files.
+    ///
+    /// If [None], the segments are committed only once the whole run has finished.
+    checkpoint_interval: Option<u64>,
+    /// Number of blocks after which the progress of every segment is reported with
+    /// [StaticFileProducerEvent::Progress]. The last event of a segment always reports all blocks
+    /// as processed.
+    ///
+    /// If [None], no progress is reported.
+    progress_interval: Option<u64>,
+    /// Whether all segments read from a single read-only database transaction that is kept open
+    /// for the whole run. Otherwise, a new transaction is opened for every chunk of blocks to
+    /// prevent long-lived read-only transactions.
+    consistent_reads: bool,
+    /// Whether a failing segment is reported with [StaticFileProducerEvent::SegmentError] instead
+    /// of failing the whole run.
+    continue_on_error: bool,
+    /// Whether a [StaticFileManifest] is written next to every static file that the run has
+    /// finalized.
+    write_manifests: bool,
+    /// Dedicated thread pool limiting the number of segments that are moved to static files in
+    /// parallel. Built once, and reused by every run.
+    ///
+    /// If [None], all segments are moved in parallel on the global thread pool.
+    thread_pool: Option<Arc<rayon::ThreadPool>>,
+    abort_handle: StaticFileProducerAbortHandle,
     listeners: EventListeners<StaticFileProducerEvent>,
+    /// Barrier that every segment waits on twice after moving a chunk of blocks, so that tests
+    /// can act deterministically between chunks.
+    #[cfg(test)]
+    chunk_barrier: Option<Arc<std::sync::Barrier>>,
+}
+
+/// Handle to abort the in-progress [StaticFileProducerInner::run].
+///
+/// Aborting while no run is in progress has no effect.
+#[derive(Debug, Clone, Default)]
+pub struct StaticFileProducerAbortHandle(Arc<AtomicBool>);
+
+impl StaticFileProducerAbortHandle {
+    /// Signals the static file producer to abort the run.
+    pub fn abort(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    fn is_aborted(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Resets the abort signal, returning `true` if it was set.
+    fn reset(&self) -> bool {
+        self.0.swap(false, Ordering::Relaxed)
+    }
 }
 
 /// Static File targets, per data part, measured in [`BlockNumber`].
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
 pub struct StaticFileTargets {
     headers: Option<RangeInclusive<BlockNumber>>,
     receipts: Option<RangeInclusive<BlockNumber>>,
@@ -81,9 +200,46 @@ impl StaticFileTargets {
         self.headers.is_some() || self.receipts.is_some() || self.transactions.is_some()
     }
 
+    /// Sets the block range of the provided segment that will be moved to static files.
+    ///
+    /// When running with [StaticFileProducerInner::run_to_directory] and the segment has no
+    /// static files in the directory yet, the block range may start at the beginning of any
+    /// static file range instead of genesis. This allows producing static files for a historical
+    /// window of blocks.
+    ///
+    /// Returns an error if the block range is empty.
+    pub fn with_segment(
+        mut self,
+        segment: StaticFileSegment,
+        block_range: RangeInclusive<BlockNumber>,
+    ) -> RethResult<Self> {
+        if block_range.is_empty() {
+            return Err(RethError::Custom(format!(
+                "empty block range {block_range:?} for {segment} static files"
+            )))
+        }
+
+        *self.as_mut(segment) = Some(block_range);
+        Ok(self)
+    }
+
+    /// Returns a mutable reference to the target of the provided segment.
+    fn as_mut(&mut self, segment: StaticFileSegment) -> &mut Option<RangeInclusive<BlockNumber>> {
+        match segment {
+            StaticFileSegment::Headers => &mut self.headers,
+            StaticFileSegment::Transactions => &mut self.transactions,
+            StaticFileSegment::Receipts => &mut self.receipts,
+        }
+    }
+
     // Returns `true` if all targets are either [`None`] or has beginning of the range equal to the
-    // highest static_file.
-    fn is_contiguous_to_highest_static_files(&self, static_files: HighestStaticFiles) -> bool {
+    // block after the highest static_file. If `allow_windows` is set, targets of segments without
+    // static files may also begin at the beginning of any static file range.
+    fn is_contiguous_to_highest_static_files(
+        &self,
+        static_files: HighestStaticFiles,
+        allow_windows: bool,
+    ) -> bool {
         [
             (self.headers.as_ref(), static_files.headers),
             (self.receipts.as_ref(), static_files.receipts),
@@ -92,10 +248,11 @@ impl StaticFileTargets {
         .iter()
         .all(|(target_block_range, highest_static_fileted_block)| {
             target_block_range.map_or(true, |target_block_range| {
-                *target_block_range.start() ==
-                    highest_static_fileted_block.map_or(0, |highest_static_fileted_block| {
-                        highest_static_fileted_block + 1
-                    })
+                let target_block_start = *target_block_range.start();
+                target_block_start == expected_block_start(*highest_static_fileted_block) ||
+                    (allow_windows &&
+                        highest_static_fileted_block.is_none() &&
+                        target_block_start == find_fixed_range(target_block_start).start())
             })
         })
     }
@@ -107,7 +264,21 @@ impl<DB: Database> StaticFileProducerInner<DB> {
         static_file_provider: StaticFileProvider,
         prune_modes: PruneModes,
     ) -> Self {
-        Self { provider_factory, static_file_provider, prune_modes, listeners: Default::default() }
+        Self {
+            provider_factory,
+            static_file_provider,
+            prune_modes,
+            checkpoint_interval: None,
+            progress_interval: None,
+            consistent_reads: false,
+            continue_on_error: false,
+            write_manifests: false,
+            thread_pool: None,
+            abort_handle: Default::default(),
+            listeners: Default::default(),
+            #[cfg(test)]
+            chunk_barrier: None,
+        }
     }
 
     /// Listen for events on the static_file_producer.
@@ -115,24 +286,390 @@ impl<DB: Database> StaticFileProducerInner<DB> {
         self.listeners.new_listener()
     }
 
+    /// Returns a handle that can be used to abort the run of the static_file_producer.
+    pub fn abort_handle(&self) -> StaticFileProducerAbortHandle {
+        self.abort_handle.clone()
+    }
+
     /// Run the static_file_producer.
     ///
     /// For each [Some] target in [StaticFileTargets], initializes a corresponding [Segment] and
     /// runs it with the provided block range using [StaticFileProvider] and a read-only
-    /// database transaction from [ProviderFactory]. All segments are run in parallel.
+    /// database transaction from [ProviderFactory]. All segments are run in parallel, unless the
+    /// maximum number of parallel segments is limited.
+    ///
+    /// If consistent reads are enabled, all segments share one read-only database transaction and
+    /// targets can't go past the highest block visible to it.
+    ///
+    /// If continue on error is enabled, the data of a failed segment is not committed, except for
+    /// the checkpoints it has reached, and the returned targets don't contain the segment.
+    /// Otherwise, the run fails with the error of the first failed segment, and nothing is
+    /// committed for any segment except for the checkpoints they have reached.
+    ///
+    /// If the run is aborted using [StaticFileProducerAbortHandle], segments stop after the chunk
+    /// of blocks that is currently being moved, everything that was moved so far is committed,
+    /// and the returned targets only contain the moved block ranges.
     ///
     /// NOTE: it doesn't delete the data from database, and the actual deleting (aka pruning) logic
     /// lives in the `prune` crate.
     pub fn run(&mut self, targets: StaticFileTargets) -> StaticFileProducerResult {
         debug_assert!(targets.is_contiguous_to_highest_static_files(
-            self.static_file_provider.get_highest_static_files()
+            self.static_file_provider.get_highest_static_files(),
+            false
         ));
 
-        self.listeners.notify(StaticFileProducerEvent::Started { targets: targets.clone() });
+        self.run_segments(targets)
+    }
+
+    /// Moves the targets to static files without checking that they are contiguous to the
+    /// highest static files. See [StaticFileProducerInner::run].
+    fn run_segments(&mut self, targets: StaticFileTargets) -> StaticFileProducerResult {
+        // Discard the abort signal that was sent while no run was in progress
+        self.abort_handle.reset();
+
+        // Provider shared by all segments if consistent reads are enabled, and the highest block
+        // visible to it
+        let consistent_provider = if self.consistent_reads {
+            let provider = self.provider_factory.provider()?.disable_long_read_transaction_safety();
+            let tip = provider.last_block_number()?;
+            if let Some(block_range) = [&targets.headers, &targets.receipts, &targets.transactions]
+                .into_iter()
+                .flatten()
+                .find(|block_range| *block_range.end() > tip)
+            {
+                return Err(ProviderError::HeaderNotFound((*block_range.end()).into()).into())
+            }
+            Some((provider, tip))
+        } else {
+            None
+        };
+
+        self.listeners.notify(StaticFileProducerEvent::Started {
+            targets: targets.clone(),
+            tip: consistent_provider.as_ref().map(|(_, tip)| *tip),
+        });
 
         debug!(target: "static_file", ?targets, "StaticFileProducer started");
         let start = Instant::now();
 
+        let segments = Self::segments(&targets);
+
+        let checkpoint_interval = self.checkpoint_interval;
+        let progress_interval = self.progress_interval;
+        let listeners = Mutex::new(&mut self.listeners);
+
+        // Highest block moved to static files per segment, or [None] if the run was aborted before
+        // any block of the segment was moved. Segments are collected individually, so that a
+        // failing segment doesn't prevent the others from finishing.
+        let move_segments = || {
+            segments
+                .par_iter()
+                .map(|(segment, block_range)| -> RethResult<Option<BlockNumber>> {
+                    debug!(target: "static_file", segment = %segment.segment(), ?block_range, "StaticFileProducer segment");
+                    let start = Instant::now();
+
+                    let chunk_size = [checkpoint_interval, progress_interval]
+                        .into_iter()
+                        .flatten()
+                        .fold(DEFAULT_CHUNK_SIZE, u64::min);
+                    let total = block_range.end() - block_range.start() + 1;
+                    let (mut processed, mut checkpointed, mut reported) = (0, 0, 0);
+                    let mut highest_block = None;
+
+                    for chunk in block_range_chunks(block_range.clone(), chunk_size) {
+                        if self.abort_handle.is_aborted() {
+                            debug!(target: "static_file", segment = %segment.segment(), ?highest_block, "StaticFileProducer segment aborted");
+                            break
+                        }
+
+                        if let Some((provider, _)) = &consistent_provider {
+                            segment.copy_to_static_files(
+                                provider,
+                                self.static_file_provider.clone(),
+                                chunk.clone(),
+                            )?;
+                        } else {
+                            // Create a new database transaction on every chunk to prevent
+                            // long-lived read-only transactions
+                            let provider = self
+                                .provider_factory
+                                .provider()?
+                                .disable_long_read_transaction_safety();
+                            segment.copy_to_static_files(
+                                &provider,
+                                self.static_file_provider.clone(),
+                                chunk.clone(),
+                            )?;
+                        }
+                        processed += chunk.end() - chunk.start() + 1;
+                        highest_block = Some(*chunk.end());
+
+                        if checkpoint_interval.is_some_and(|interval| {
+                            processed - checkpointed >= interval || processed == total
+                        }) {
+                            self.static_file_provider.latest_writer(segment.segment())?.commit()?;
+                            checkpointed = processed;
+
+                            trace!(target: "static_file", segment = %segment.segment(), block_number = chunk.end(), "StaticFileProducer checkpoint");
+                            listeners.lock().notify(StaticFileProducerEvent::Checkpoint {
+                                segment: segment.segment(),
+                                block_number: *chunk.end(),
+                            });
+                        }
+
+                        if progress_interval.is_some_and(|interval| {
+                            processed - reported >= interval || processed == total
+                        }) {
+                            reported = processed;
+
+                            trace!(target: "static_file", segment = %segment.segment(), processed, total, "StaticFileProducer progress");
+                            listeners.lock().notify(StaticFileProducerEvent::Progress {
+                                segment: segment.segment(),
+                                processed,
+                                total,
+                            });
+                        }
+
+                        #[cfg(test)]
+                        if let Some(barrier) = &self.chunk_barrier {
+                            barrier.wait();
+                            barrier.wait();
+                        }
+                    }
+
+                    let elapsed = start.elapsed(); // TODO(alexey): track in metrics
+                    debug!(target: "static_file", segment = %segment.segment(), ?block_range, ?elapsed, "Finished StaticFileProducer segment");
+
+                    Ok(highest_block)
+                })
+                .collect::<Vec<_>>()
+        };
+        let highest_blocks = match &self.thread_pool {
+            Some(thread_pool) => thread_pool.install(move_segments),
+            None => move_segments(),
+        };
+
+        let mut moved_targets =
+            StaticFileTargets { headers: None, receipts: None, transactions: None };
+        let mut moved_segments = Vec::new();
+        for ((segment, block_range), highest_block) in segments.iter().zip(highest_blocks) {
+            match highest_block {
+                Ok(Some(highest_block)) => {
+                    moved_segments.push((segment, block_range, highest_block))
+                }
+                Ok(None) => {}
+                Err(error) => {
+                    if !self.continue_on_error {
+                        // Nothing is committed past the last checkpoints, so roll the writers of
+                        // all segments back to their last committed state. Otherwise, the rows and
+                        // blocks they appended would be persisted by a later commit, and the next
+                        // run couldn't resume from the highest static files.
+                        for (segment, _) in &segments {
+                            self.static_file_provider.discard_writer(segment.segment());
+                        }
+
+                        let elapsed = start.elapsed(); // TODO(alexey): track in metrics
+                        debug!(target: "static_file", segment = %segment.segment(), %error, ?elapsed, "StaticFileProducer failed");
+
+                        self.listeners.notify(StaticFileProducerEvent::Failed {
+                            segment: segment.segment(),
+                            error: error.clone(),
+                            elapsed,
+                        });
+                        return Err(error)
+                    }
+
+                    // Roll the writer back to its last committed state, so that the rows and
+                    // blocks appended before the failure are not persisted by a later commit.
+                    self.static_file_provider.discard_writer(segment.segment());
+
+                    debug!(target: "static_file", segment = %segment.segment(), %error, "StaticFileProducer segment failed");
+                    self.listeners.notify(StaticFileProducerEvent::SegmentError {
+                        segment: segment.segment(),
+                        error,
+                    });
+                }
+            }
+        }
+
+        for (segment, block_range, highest_block) in moved_segments {
+            self.static_file_provider.latest_writer(segment.segment())?.commit()?;
+            self.static_file_provider.update_index(segment.segment(), Some(highest_block))?;
+
+            let block_range = *block_range.start()..=highest_block;
+            let mut files = Vec::new();
+            for jar in self.static_files(segment.segment(), &block_range)? {
+                files.extend(static_file_paths(&jar));
+
+                // Files that still receive blocks would be re-hashed on every run, so manifests are
+                // only written once all blocks of the fixed range are in the static file
+                let header = jar.user_header();
+                if self.write_manifests && header.block_end() == Some(header.expected_block_end()) {
+                    let manifest_path = StaticFileManifest::path(jar.data_path());
+                    StaticFileManifest::new(&jar)?.write(&manifest_path)?;
+                    files.push(manifest_path);
+                }
+            }
+            self.listeners.notify(StaticFileProducerEvent::SegmentFinished {
+                segment: segment.segment(),
+                block_range: block_range.clone(),
+                files,
+            });
+
+            *moved_targets.as_mut(segment.segment()) = Some(block_range);
+        }
+
+        let elapsed = start.elapsed(); // TODO(alexey): track in metrics
+
+        if self.abort_handle.reset() && moved_targets != targets {
+            debug!(target: "static_file", ?targets, ?moved_targets, ?elapsed, "StaticFileProducer aborted");
+
+            self.listeners.notify(StaticFileProducerEvent::Aborted {
+                targets: moved_targets.clone(),
+                elapsed,
+            });
+        } else {
+            debug!(target: "static_file", ?targets, ?elapsed, "StaticFileProducer finished");
+
+            self.listeners.notify(StaticFileProducerEvent::Finished {
+                targets: moved_targets.clone(),
+                elapsed,
+            });
+        }
+
+        Ok(moved_targets)
+    }
+
+    /// Run the static_file_producer, writing static files to the provided directory instead of the
+    /// directory of the configured [StaticFileProvider].
+    ///
+    /// The directory is created if it doesn't exist. Targets must be contiguous to the static
+    /// files that already exist in the directory, or start at the beginning of a static file range
+    /// for segments without static files in it. The produced static files can be read by opening
+    /// a [StaticFileProvider] at the same directory.
+    pub fn run_to_directory(
+        &mut self,
+        targets: StaticFileTargets,
+        directory: impl AsRef<Path>,
+    ) -> StaticFileProducerResult {
+        let directory = directory.as_ref();
+        if directory.exists() && !directory.is_dir() {
+            return Err(RethError::Custom(format!(
+                "static files output path {} is not a directory",
+                directory.display()
+            )))
+        }
+        reth_primitives::fs::create_dir_all(directory)?;
+        check_writable(directory)?;
+
+        let output_provider = self.static_file_provider.with_directory(directory)?;
+        let static_file_provider =
+            std::mem::replace(&mut self.static_file_provider, output_provider);
+
+        // Restores the configured static file provider even if the run panics
+        let mut guard = StaticFileProviderGuard {
+            producer: self,
+            static_file_provider: Some(static_file_provider),
+        };
+        debug_assert!(targets.is_contiguous_to_highest_static_files(
+            guard.producer.static_file_provider.get_highest_static_files(),
+            true
+        ));
+        guard.producer.run_segments(targets)
+    }
+
+    /// Estimate the amount of data that [StaticFileProducerInner::run] would move to static files
+    /// for the provided targets, without writing anything.
+    ///
+    /// Sizes are approximated using a sample of the most recent database rows of every segment.
+    /// Headers are sampled from the static files instead if the database has none. Sizes don't
+    /// account for compression, so they're an upper bound if static files are compressed.
+    pub fn estimate(&self, targets: &StaticFileTargets) -> RethResult<StaticFileEstimate> {
+        let provider = self.provider_factory.provider()?;
+
+        let mut estimate = StaticFileEstimate::new();
+        for (segment, block_range) in Self::segments(targets) {
+            estimate.insert(segment.segment(), segment.estimate(&provider, block_range)?);
+        }
+
+        trace!(target: "static_file", ?targets, ?estimate, "StaticFile estimate");
+
+        Ok(estimate)
+    }
+
+    /// Validate the targets without moving any data to static files.
+    ///
+    /// Checks that the targets are contiguous to the highest static files, that all targeted
+    /// blocks exist in the database, and that the static files directory is writable. Emits
+    /// [StaticFileProducerEvent::DryRun] with the estimated amount of data that
+    /// [StaticFileProducerInner::run] would move.
+    pub fn dry_run(&mut self, targets: StaticFileTargets) -> RethResult<StaticFileEstimate> {
+        debug!(target: "static_file", ?targets, "StaticFileProducer dry run");
+
+        let highest_static_files = self.static_file_provider.get_highest_static_files();
+        let provider = self.provider_factory.provider()?;
+
+        let mut estimate = StaticFileEstimate::new();
+        for (segment, block_range) in Self::segments(&targets) {
+            let expected_block_start =
+                expected_block_start(highest_static_files.highest(segment.segment()));
+            if *block_range.start() != expected_block_start {
+                return Err(ProviderError::UnexpectedStaticFileBlockNumber(
+                    segment.segment(),
+                    *block_range.start(),
+                    expected_block_start,
+                )
+                .into())
+            }
+
+            // Transaction based segments check the targeted blocks when estimating
+            if segment.segment().is_headers() {
+                provider
+                    .tx_ref()
+                    .get::<tables::CanonicalHeaders>(*block_range.end())?
+                    .ok_or_else(|| ProviderError::HeaderNotFound((*block_range.end()).into()))?;
+            }
+
+            estimate.insert(segment.segment(), segment.estimate(&provider, block_range)?);
+        }
+
+        check_writable(self.static_file_provider.directory())?;
+
+        self.listeners.notify(StaticFileProducerEvent::DryRun {
+            targets: targets.clone(),
+            estimate: estimate.clone(),
+        });
+
+        Ok(estimate)
+    }
+
+    /// Returns all static files that contain the provided block range of the segment.
+    fn static_files(
+        &self,
+        segment: StaticFileSegment,
+        block_range: &RangeInclusive<BlockNumber>,
+    ) -> RethResult<Vec<NippyJar<SegmentHeader>>> {
+        let mut jars = Vec::new();
+
+        let mut block = *block_range.start();
+        while block <= *block_range.end() {
+            let fixed_range = find_fixed_range(block);
+            let jar = NippyJar::<SegmentHeader>::load(
+                &self.static_file_provider.directory().join(segment.filename(&fixed_range)),
+            )
+            .map_err(|e| ProviderError::NippyJar(e.to_string()))?;
+            jars.push(jar);
+
+            block = fixed_range.end() + 1;
+        }
+
+        Ok(jars)
+    }
+
+    /// Returns a [Segment] with its block range for every [Some] target in [StaticFileTargets].
+    fn segments(
+        targets: &StaticFileTargets,
+    ) -> Vec<(Box<dyn Segment<DB>>, RangeInclusive<BlockNumber>)> {
         let mut segments = Vec::<(Box<dyn Segment<DB>>, RangeInclusive<BlockNumber>)>::new();
 
         if let Some(block_range) = targets.transactions.clone() {
@@ -145,33 +682,209 @@ impl<DB: Database> StaticFileProducerInner<DB> {
             segments.push((Box::new(segments::Receipts), block_range));
         }
 
-        segments.par_iter().try_for_each(|(segment, block_range)| -> RethResult<()> {
-            debug!(target: "static_file", segment = %segment.segment(), ?block_range, "StaticFileProducer segment");
-            let start = Instant::now();
+        segments
+    }
 
-            // Create a new database transaction on every segment to prevent long-lived read-only
-            // transactions
-            let provider = self.provider_factory.provider()?.disable_long_read_transaction_safety();
-            segment.copy_to_static_files(provider, self.static_file_provider.clone(), block_range.clone())?;
+    /// Verify the integrity of static files for the provided targets.
+    ///
+    /// For every static file covering any of the targeted block ranges, checks that it covers all
+    /// targeted blocks, that its transaction range and number of rows match its blocks and their
+    /// transactions in the database, and that its offsets and data files are not truncated. Every
+    /// failed check is reported with [StaticFileProducerEvent::VerificationFailed].
+    ///
+    /// Returns `true` if all static files passed the verification.
+    pub fn verify(&mut self, targets: &StaticFileTargets) -> RethResult<bool> {
+        let mut failures = Vec::new();
 
-            let elapsed = start.elapsed(); // TODO(alexey): track in metrics
-            debug!(target: "static_file", segment = %segment.segment(), ?block_range, ?elapsed, "Finished StaticFileProducer segment");
+        for (segment, block_range) in [
+            (StaticFileSegment::Headers, targets.headers.as_ref()),
+            (StaticFileSegment::Transactions, targets.transactions.as_ref()),
+            (StaticFileSegment::Receipts, targets.receipts.as_ref()),
+        ] {
+            let Some(block_range) = block_range else { continue };
 
-            Ok(())
-        })?;
+            let mut block = *block_range.start();
+            while block <= *block_range.end() {
+                let fixed_range = find_fixed_range(block);
+                let covered_range = block..=fixed_range.end().min(*block_range.end());
+
+                if let Some(reason) =
+                    self.verify_static_file(segment, &fixed_range, &covered_range)?
+                {
+                    debug!(target: "static_file", %segment, block_range = ?covered_range, ?reason, "StaticFile verification failed");
+                    failures.push(StaticFileProducerEvent::VerificationFailed {
+                        segment,
+                        block_range: covered_range,
+                        reason,
+                    });
+                }
 
-        self.static_file_provider.commit()?;
-        for (segment, block_range) in segments {
-            self.static_file_provider.update_index(segment.segment(), Some(*block_range.end()))?;
+                block = fixed_range.end() + 1;
+            }
         }
 
-        let elapsed = start.elapsed(); // TODO(alexey): track in metrics
-        debug!(target: "static_file", ?targets, ?elapsed, "StaticFileProducer finished");
+        let verified = failures.is_empty();
+        for failure in failures {
+            self.listeners.notify(failure);
+        }
 
-        self.listeners
-            .notify(StaticFileProducerEvent::Finished { targets: targets.clone(), elapsed });
+        Ok(verified)
+    }
 
-        Ok(targets)
+    /// Verify the files of a static file against its [StaticFileManifest] at the provided path.
+    ///
+    /// Files are resolved relative to the directory of the manifest. Every file that is missing or
+    /// whose content hash doesn't match the manifest is reported with
+    /// [StaticFileProducerEvent::VerificationFailed].
+    ///
+    /// Returns `true` if all files match the manifest, and an error if the manifest doesn't list
+    /// the data, offsets and configuration files of the static file.
+    pub fn verify_manifest(&mut self, path: impl AsRef<Path>) -> RethResult<bool> {
+        let path = path.as_ref();
+        let manifest = StaticFileManifest::load(path)?;
+        let directory = path.parent().unwrap_or_else(|| Path::new(""));
+
+        // Data, offsets and configuration files are required, while the index file only exists if
+        // the static file uses filters
+        let data_file_name =
+            manifest.segment.filename(&find_fixed_range(*manifest.block_range.start()));
+        for required_file_name in [
+            data_file_name.clone(),
+            format!("{data_file_name}.{OFFSETS_FILE_EXTENSION}"),
+            format!("{data_file_name}.{CONFIG_FILE_EXTENSION}"),
+        ] {
+            if !manifest.files.iter().any(|file| file.name == required_file_name) {
+                return Err(RethError::Custom(format!(
+                    "manifest {} doesn't list file {required_file_name:?}",
+                    path.display()
+                )))
+            }
+        }
+
+        let mut failures = Vec::new();
+        for file in &manifest.files {
+            // Only files in the directory of the manifest can be listed
+            if Path::new(&file.name).file_name() != Some(OsStr::new(&file.name)) {
+                return Err(RethError::Custom(format!(
+                    "manifest {} lists invalid file name {:?}",
+                    path.display(),
+                    file.name
+                )))
+            }
+
+            let file_path = directory.join(&file.name);
+            let got = if file_path.exists() { Some(hash_file(&file_path)?) } else { None };
+
+            if got != Some(file.hash) {
+                let reason = StaticFileVerificationFailure::HashMismatch {
+                    file: file.name.clone(),
+                    expected: file.hash,
+                    got,
+                };
+                debug!(target: "static_file", segment = %manifest.segment, block_range = ?manifest.block_range, ?reason, "StaticFile manifest verification failed");
+                failures.push(StaticFileProducerEvent::VerificationFailed {
+                    segment: manifest.segment,
+                    block_range: manifest.block_range.clone(),
+                    reason,
+                });
+            }
+        }
+
+        let verified = failures.is_empty();
+        for failure in failures {
+            self.listeners.notify(failure);
+        }
+
+        Ok(verified)
+    }
+
+    /// Verifies a single static file of the segment with the provided fixed block range. The
+    /// static file is loaded from disk, bypassing any cached providers.
+    fn verify_static_file(
+        &self,
+        segment: StaticFileSegment,
+        fixed_range: &SegmentRangeInclusive,
+        block_range: &RangeInclusive<BlockNumber>,
+    ) -> RethResult<Option<StaticFileVerificationFailure>> {
+        let path = self.static_file_provider.directory().join(segment.filename(fixed_range));
+        if !path.exists() {
+            return Ok(Some(StaticFileVerificationFailure::MissingFile))
+        }
+
+        let jar = NippyJar::<SegmentHeader>::load(&path)
+            .map_err(|e| ProviderError::NippyJar(e.to_string()))?;
+        let header = jar.user_header();
+
+        let Some(jar_block_range) =
+            header.block_range().map(|range| range.start()..=range.end()).filter(|range| {
+                range.start() <= block_range.start() && range.end() >= block_range.end()
+            })
+        else {
+            return Ok(Some(StaticFileVerificationFailure::BlockRangeMismatch {
+                got: header.block_range().map(|range| range.start()..=range.end()),
+            }))
+        };
+
+        // Expected transactions of the static file are derived from the database, so that a
+        // static file with a wrong transaction range in its header doesn't pass
+        let expected_rows = match segment {
+            // Headers are written to static files directly and pruned from the database, so there
+            // is one row per block of the static file
+            StaticFileSegment::Headers => jar_block_range.count(),
+            StaticFileSegment::Transactions | StaticFileSegment::Receipts => {
+                let provider = self.provider_factory.provider()?;
+                let first_block_body_indices = provider
+                    .block_body_indices(*jar_block_range.start())?
+                    .ok_or(ProviderError::BlockBodyIndicesNotFound(*jar_block_range.start()))?;
+                let last_block_body_indices = provider
+                    .block_body_indices(*jar_block_range.end())?
+                    .ok_or(ProviderError::BlockBodyIndicesNotFound(*jar_block_range.end()))?;
+                let expected_tx_range = (last_block_body_indices.next_tx_num() >
+                    first_block_body_indices.first_tx_num())
+                .then(|| {
+                    first_block_body_indices.first_tx_num()..=last_block_body_indices.last_tx_num()
+                });
+
+                let tx_range = header.tx_range().map(|range| range.start()..=range.end());
+                if tx_range != expected_tx_range {
+                    return Ok(Some(StaticFileVerificationFailure::TxRangeMismatch {
+                        expected: expected_tx_range,
+                        got: tx_range,
+                    }))
+                }
+
+                expected_tx_range.map_or(0, |range| range.count())
+            }
+        };
+        if jar.rows() != expected_rows {
+            return Ok(Some(StaticFileVerificationFailure::RowCountMismatch {
+                expected: expected_rows,
+                got: jar.rows(),
+            }))
+        }
+
+        let reader = jar.open_data_reader().map_err(|e| ProviderError::NippyJar(e.to_string()))?;
+
+        // One offset per column of every row, and the last one is the size of the data file
+        let expected_offsets = jar.rows() * jar.columns() + 1;
+        let offsets = reader.offsets_count().map_err(|e| ProviderError::NippyJar(e.to_string()))?;
+        if offsets != expected_offsets {
+            return Ok(Some(StaticFileVerificationFailure::OffsetsCountMismatch {
+                expected: expected_offsets,
+                got: offsets,
+            }))
+        }
+
+        let expected_size =
+            reader.reverse_offset(0).map_err(|e| ProviderError::NippyJar(e.to_string()))? as usize;
+        if reader.size() != expected_size {
+            return Ok(Some(StaticFileVerificationFailure::DataSizeMismatch {
+                expected: expected_size,
+                got: reader.size(),
+            }))
+        }
+
+        Ok(None)
     }
 
     /// Returns a static file targets at the provided finalized block numbers per segment.
@@ -230,31 +943,127 @@ impl<DB: Database> StaticFileProducerInner<DB> {
     }
 }
 
+/// Returns the block that the target of a segment must start at, given the highest static file
+/// block of the segment.
+fn expected_block_start(highest_static_file_block: Option<BlockNumber>) -> BlockNumber {
+    highest_static_file_block.map_or(0, |block| block + 1)
+}
+
+/// Puts back the [StaticFileProvider] of a [StaticFileProducerInner] that was swapped out by
+/// [StaticFileProducerInner::run_to_directory] when dropped.
+struct StaticFileProviderGuard<'a, DB> {
+    producer: &'a mut StaticFileProducerInner<DB>,
+    static_file_provider: Option<StaticFileProvider>,
+}
+
+impl<DB> Drop for StaticFileProviderGuard<'_, DB> {
+    fn drop(&mut self) {
+        if let Some(static_file_provider) = self.static_file_provider.take() {
+            self.producer.static_file_provider = static_file_provider;
+        }
+    }
+}
+
+/// Returns paths of all files of the static file.
+pub(crate) fn static_file_paths(jar: &NippyJar<SegmentHeader>) -> Vec<PathBuf> {
+    let mut files = vec![jar.data_path().to_path_buf(), jar.offsets_path(), jar.config_path()];
+    // Index file only exists if the static file uses filters
+    if jar.index_path().exists() {
+        files.push(jar.index_path());
+    }
+    files
+}
+
+/// Checks that files can be created in the directory by creating and removing a probe file in it.
+/// Unlike the permission bits of the directory, this accounts for ownership, ACLs and read-only
+/// mounts.
+fn check_writable(directory: &Path) -> RethResult<()> {
+    let probe_path = directory.join(WRITE_PROBE_FILE_NAME);
+    std::fs::OpenOptions::new().write(true).create(true).truncate(true).open(&probe_path).map_err(
+        |err| {
+            RethError::Custom(format!(
+                "static files directory {} is not writable: {err}",
+                directory.display()
+            ))
+        },
+    )?;
+    reth_primitives::fs::remove_file(probe_path)?;
+    Ok(())
+}
+
+/// Builds the thread pool that moves at most `max_parallel_segments` segments in parallel.
+fn segments_thread_pool(max_parallel_segments: usize) -> RethResult<Arc<rayon::ThreadPool>> {
+    if max_parallel_segments == 0 {
+        return Err(RethError::Custom(
+            "maximum number of parallel static file segments must be greater than zero".to_string(),
+        ))
+    }
+
+    let thread_pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(max_parallel_segments)
+        .build()
+        .map_err(|err| {
+            RethError::Custom(format!("failed to build static file producer thread pool: {err}"))
+        })?;
+    Ok(Arc::new(thread_pool))
+}
+
+/// Name of the file that is created to check that a static files directory is writable.
+const WRITE_PROBE_FILE_NAME: &str = ".static_file_producer_write_probe";
+
+/// Maximum number of blocks that are moved to static files using a single database transaction.
+/// Aborting the run is checked between the chunks.
+const DEFAULT_CHUNK_SIZE: u64 = 10_000;
+
+/// Splits the block range into consecutive chunks of at most `chunk_size` blocks.
+fn block_range_chunks(
+    block_range: RangeInclusive<BlockNumber>,
+    chunk_size: u64,
+) -> impl Iterator<Item = RangeInclusive<BlockNumber>> {
+    let chunk_size = chunk_size.max(1);
+    let end = *block_range.end();
+    block_range
+        .step_by(usize::try_from(chunk_size).unwrap_or(usize::MAX))
+        .map(move |start| start..=start.saturating_add(chunk_size - 1).min(end))
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::static_file_producer::{
-        StaticFileProducer, StaticFileProducerInner, StaticFileTargets,
+    use crate::{
+        static_file_producer::{StaticFileProducer, StaticFileProducerInner, StaticFileTargets},
+        StaticFileManifest, StaticFileProducerEvent, StaticFileVerificationFailure,
     };
     use assert_matches::assert_matches;
-    use reth_db::{database::Database, test_utils::TempDatabase, transaction::DbTx, DatabaseEnv};
+    use reth_db::{
+        cursor::DbCursorRO,
+        database::Database,
+        models::StoredBlockBodyIndices,
+        tables,
+        test_utils::TempDatabase,
+        transaction::{DbTx, DbTxMut},
+        DatabaseEnv,
+    };
     use reth_interfaces::{
         provider::ProviderError,
         test_utils::{
             generators,
-            generators::{random_block_range, random_receipt},
+            generators::{random_block, random_block_range, random_receipt},
         },
         RethError,
     };
+    use reth_nippy_jar::NippyJar;
     use reth_primitives::{
-        static_file::HighestStaticFiles, PruneModes, StaticFileSegment, B256, U256,
+        static_file::{find_fixed_range, HighestStaticFiles, SegmentHeader},
+        BlockNumber, PruneModes, StaticFileSegment, TxNumber, B256, U256,
     };
     use reth_provider::{
-        providers::{StaticFileProvider, StaticFileWriter},
-        ProviderFactory,
+        providers::{StaticFileCompression, StaticFileProvider, StaticFileWriter},
+        HeaderProvider, ProviderFactory, TransactionsProvider,
     };
     use reth_stages::test_utils::{StorageKind, TestStageDB};
     use std::{
-        sync::{mpsc::channel, Arc},
+        ops::Range,
+        sync::{mpsc::channel, Arc, Barrier},
         time::Duration,
     };
     use tempfile::TempDir;
@@ -294,6 +1103,38 @@ mod tests {
         (provider_factory, static_file_provider, db.temp_static_files_dir)
     }
 
+    /// Inserts the body of a random block into the database, returning the range of its
+    /// transactions.
+    fn insert_block_body(
+        provider_factory: &ProviderFactory<Arc<TempDatabase<DatabaseEnv>>>,
+        number: BlockNumber,
+    ) -> Range<TxNumber> {
+        let mut rng = generators::rng();
+        let block = random_block(&mut rng, number, None, Some(2), Some(0));
+
+        let tx = provider_factory.db_ref().tx_mut().expect("init tx");
+        let first_tx_num = tx
+            .cursor_read::<tables::BlockBodyIndices>()
+            .expect("init cursor")
+            .last()
+            .expect("get last block body indices")
+            .map(|(_, indices)| indices.next_tx_num())
+            .unwrap_or_default();
+        let block_body_indices =
+            StoredBlockBodyIndices { first_tx_num, tx_count: block.body.len() as u64 };
+        for (tx_number, transaction) in block_body_indices.tx_num_range().zip(block.body) {
+            tx.put::<tables::Transactions>(tx_number, transaction.into())
+                .expect("insert transaction");
+        }
+        tx.put::<tables::TransactionBlocks>(block_body_indices.last_tx_num(), number)
+            .expect("insert transaction block");
+        tx.put::<tables::BlockBodyIndices>(number, block_body_indices.clone())
+            .expect("insert block body indices");
+        tx.commit().expect("commit tx");
+
+        block_body_indices.tx_num_range()
+    }
+
     #[test]
     fn run() {
         let (provider_factory, static_file_provider, _temp_static_files_dir) = setup();
@@ -371,6 +1212,1009 @@ mod tests {
         );
     }
 
+    #[test]
+    fn run_with_checkpoints() {
+        let (provider_factory, static_file_provider, _temp_static_files_dir) = setup();
+
+        let mut static_file_producer = StaticFileProducerInner::new(
+            provider_factory.clone(),
+            static_file_provider.clone(),
+            PruneModes::default(),
+        );
+        static_file_producer.checkpoint_interval = Some(2);
+        let mut events = static_file_producer.events().into_inner();
+
+        // Block 4 doesn't exist, so the run fails after the first two chunks have been committed
+        let targets =
+            StaticFileTargets { headers: None, receipts: None, transactions: Some(0..=4) };
+        assert_matches!(
+            static_file_producer.run(targets),
+            Err(RethError::Provider(ProviderError::BlockBodyIndicesNotFound(4)))
+        );
+        assert_eq!(
+            static_file_provider.get_highest_static_files(),
+            HighestStaticFiles { headers: None, receipts: None, transactions: Some(3) }
+        );
+
+        assert_matches!(events.try_recv(), Ok(StaticFileProducerEvent::Started { .. }));
+        for block_number in [1, 3] {
+            assert_eq!(
+                events.try_recv(),
+                Ok(StaticFileProducerEvent::Checkpoint {
+                    segment: StaticFileSegment::Transactions,
+                    block_number
+                })
+            );
+        }
+        assert_matches!(
+            events.try_recv(),
+            Ok(StaticFileProducerEvent::Failed {
+                segment: StaticFileSegment::Transactions,
+                error: RethError::Provider(ProviderError::BlockBodyIndicesNotFound(4)),
+                ..
+            })
+        );
+        assert!(events.try_recv().is_err());
+
+        // Changes made after the last checkpoint are discarded, so committing the static file
+        // provider doesn't persist them
+        static_file_provider.commit().expect("commit static files");
+        assert_eq!(
+            static_file_provider.get_highest_static_files(),
+            HighestStaticFiles { headers: None, receipts: None, transactions: Some(3) }
+        );
+
+        // The next run resumes from the last checkpoint
+        let tx_range = insert_block_body(&provider_factory, 4);
+        let targets = static_file_producer
+            .get_static_file_targets(HighestStaticFiles {
+                headers: None,
+                receipts: None,
+                transactions: Some(4),
+            })
+            .expect("get static file targets");
+        assert_eq!(
+            targets,
+            StaticFileTargets { headers: None, receipts: None, transactions: Some(4..=4) }
+        );
+        assert_eq!(static_file_producer.run(targets.clone()), Ok(targets));
+        assert_eq!(
+            static_file_provider.get_highest_static_files(),
+            HighestStaticFiles { headers: None, receipts: None, transactions: Some(4) }
+        );
+        let db_transactions = provider_factory
+            .db_ref()
+            .tx()
+            .expect("init tx")
+            .cursor_read::<tables::Transactions>()
+            .expect("init cursor")
+            .walk_range(0..tx_range.end)
+            .expect("walk transactions")
+            .map(|entry| entry.map(|(_, transaction)| transaction))
+            .collect::<Result<Vec<_>, _>>()
+            .expect("read transactions");
+        assert_eq!(
+            static_file_provider.transactions_by_tx_range(0..tx_range.end),
+            Ok(db_transactions)
+        );
+    }
+
+    #[test]
+    fn run_with_progress() {
+        let (provider_factory, static_file_provider, _temp_static_files_dir) = setup();
+
+        let mut static_file_producer = StaticFileProducerInner::new(
+            provider_factory,
+            static_file_provider,
+            PruneModes::default(),
+        );
+        static_file_producer.progress_interval = Some(3);
+        let mut events = static_file_producer.events().into_inner();
+
+        let targets =
+            StaticFileTargets { headers: Some(0..=3), receipts: None, transactions: None };
+        assert_matches!(static_file_producer.run(targets), Ok(_));
+
+        assert_matches!(events.try_recv(), Ok(StaticFileProducerEvent::Started { .. }));
+        for processed in [3, 4] {
+            assert_eq!(
+                events.try_recv(),
+                Ok(StaticFileProducerEvent::Progress {
+                    segment: StaticFileSegment::Headers,
+                    processed,
+                    total: 4
+                })
+            );
+        }
+        assert_matches!(events.try_recv(), Ok(StaticFileProducerEvent::SegmentFinished { .. }));
+        assert_matches!(events.try_recv(), Ok(StaticFileProducerEvent::Finished { .. }));
+    }
+
+    #[test]
+    fn verify() {
+        let (provider_factory, static_file_provider, _temp_static_files_dir) = setup();
+
+        let mut static_file_producer = StaticFileProducerInner::new(
+            provider_factory.clone(),
+            static_file_provider.clone(),
+            PruneModes::default(),
+        );
+        let mut events = static_file_producer.events().into_inner();
+
+        let targets =
+            StaticFileTargets { headers: Some(0..=3), receipts: None, transactions: Some(0..=3) };
+        assert_matches!(static_file_producer.run(targets.clone()), Ok(_));
+        assert_matches!(static_file_producer.verify(&targets), Ok(true));
+
+        // Truncate the data file of the headers static file
+        let data_path = static_file_provider
+            .directory()
+            .join(StaticFileSegment::Headers.filename(&find_fixed_range(0)));
+        let data_file = std::fs::OpenOptions::new().write(true).open(data_path).unwrap();
+        data_file.set_len(data_file.metadata().unwrap().len() - 1).unwrap();
+
+        assert_matches!(static_file_producer.verify(&targets), Ok(false));
+
+        let mut failures = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            if let StaticFileProducerEvent::VerificationFailed { segment, block_range, reason } =
+                event
+            {
+                failures.push((segment, block_range, reason));
+            }
+        }
+        assert_matches!(
+            failures.as_slice(),
+            [(
+                StaticFileSegment::Headers,
+                block_range,
+                StaticFileVerificationFailure::DataSizeMismatch { .. }
+            )] if *block_range == (0..=3)
+        );
+
+        // Add a transaction to the last block in the database, so the transaction range of the
+        // transactions static file doesn't match it anymore
+        let tx = provider_factory.db_ref().tx_mut().expect("init tx");
+        let mut block_body_indices =
+            tx.get::<tables::BlockBodyIndices>(3).expect("get block body indices").unwrap();
+        let got = static_file_provider
+            .get_highest_static_file_tx(StaticFileSegment::Transactions)
+            .map(|tx_end| 0..=tx_end);
+        block_body_indices.tx_count += 1;
+        tx.put::<tables::BlockBodyIndices>(3, block_body_indices.clone())
+            .expect("insert block body indices");
+        tx.commit().expect("commit tx");
+
+        let targets =
+            StaticFileTargets { headers: None, receipts: None, transactions: Some(0..=3) };
+        assert_matches!(static_file_producer.verify(&targets), Ok(false));
+        assert_eq!(
+            events.try_recv(),
+            Ok(StaticFileProducerEvent::VerificationFailed {
+                segment: StaticFileSegment::Transactions,
+                block_range: 0..=3,
+                reason: StaticFileVerificationFailure::TxRangeMismatch {
+                    expected: Some(0..=block_body_indices.last_tx_num()),
+                    got,
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn verify_without_database_headers() {
+        let db = TestStageDB::default();
+        let blocks = random_block_range(&mut generators::rng(), 0..=3, B256::ZERO, 2..3);
+        db.insert_blocks(blocks.iter(), StorageKind::Static).expect("insert blocks");
+
+        // Headers and transactions are only in static files
+        let tx = db.factory.db_ref().tx().expect("init tx");
+        assert_eq!(tx.entries::<tables::CanonicalHeaders>().unwrap(), 0);
+        assert_eq!(tx.entries::<tables::Transactions>().unwrap(), 0);
+        drop(tx);
+
+        let static_file_provider = db.factory.static_file_provider();
+        let mut static_file_producer = StaticFileProducerInner::new(
+            db.factory.clone(),
+            static_file_provider,
+            PruneModes::default(),
+        );
+
+        let targets =
+            StaticFileTargets { headers: Some(0..=3), receipts: None, transactions: Some(0..=3) };
+        assert_matches!(static_file_producer.verify(&targets), Ok(true));
+    }
+
+    #[test]
+    fn abort() {
+        let (provider_factory, static_file_provider, _temp_static_files_dir) = setup();
+
+        let mut static_file_producer = StaticFileProducerInner::new(
+            provider_factory,
+            static_file_provider.clone(),
+            PruneModes::default(),
+        );
+        let mut events = static_file_producer.events().into_inner();
+
+        let targets = StaticFileTargets {
+            headers: Some(0..=3),
+            receipts: Some(0..=3),
+            transactions: Some(0..=3),
+        };
+
+        // Aborting while no run is in progress doesn't affect the next run
+        static_file_producer.abort_handle().abort();
+        assert_eq!(static_file_producer.run(targets.clone()), Ok(targets.clone()));
+        assert_eq!(
+            static_file_provider.get_highest_static_files(),
+            HighestStaticFiles { headers: Some(3), receipts: Some(3), transactions: Some(3) }
+        );
+
+        assert_matches!(events.try_recv(), Ok(StaticFileProducerEvent::Started { .. }));
+        while let Ok(event) = events.try_recv() {
+            assert_matches!(
+                event,
+                StaticFileProducerEvent::SegmentFinished { .. } |
+                    StaticFileProducerEvent::Finished { .. }
+            );
+        }
+    }
+
+    #[test]
+    fn abort_between_chunks() {
+        let (provider_factory, static_file_provider, _temp_static_files_dir) = setup();
+
+        let mut static_file_producer = StaticFileProducerInner::new(
+            provider_factory,
+            static_file_provider.clone(),
+            PruneModes::default(),
+        );
+        static_file_producer.checkpoint_interval = Some(1);
+        let mut events = static_file_producer.events().into_inner();
+        let abort_handle = static_file_producer.abort_handle();
+        let chunk_barrier = Arc::new(Barrier::new(2));
+        static_file_producer.chunk_barrier = Some(chunk_barrier.clone());
+
+        let targets =
+            StaticFileTargets { headers: Some(0..=3), receipts: None, transactions: None };
+        let run = std::thread::spawn(move || static_file_producer.run(targets));
+        assert_matches!(events.blocking_recv(), Some(StaticFileProducerEvent::Started { .. }));
+
+        // Wait for the first chunk to be moved, and abort the run before the next one
+        chunk_barrier.wait();
+        abort_handle.abort();
+        chunk_barrier.wait();
+
+        // Only the first chunk is moved and committed
+        let moved_targets =
+            StaticFileTargets { headers: Some(0..=0), receipts: None, transactions: None };
+        assert_eq!(run.join().unwrap(), Ok(moved_targets.clone()));
+        assert_eq!(
+            StaticFileProvider::new(static_file_provider.directory())
+                .unwrap()
+                .get_highest_static_files(),
+            HighestStaticFiles { headers: Some(0), receipts: None, transactions: None }
+        );
+
+        assert_eq!(
+            events.try_recv(),
+            Ok(StaticFileProducerEvent::Checkpoint {
+                segment: StaticFileSegment::Headers,
+                block_number: 0
+            })
+        );
+        assert_matches!(events.try_recv(), Ok(StaticFileProducerEvent::SegmentFinished { .. }));
+        assert_matches!(
+            events.try_recv(),
+            Ok(StaticFileProducerEvent::Aborted { targets, .. }) if targets == moved_targets
+        );
+    }
+
+    #[test]
+    fn estimate() {
+        let (provider_factory, static_file_provider, _temp_static_files_dir) = setup();
+
+        let mut static_file_producer = StaticFileProducerInner::new(
+            provider_factory.clone(),
+            static_file_provider.clone(),
+            PruneModes::default(),
+        );
+
+        let targets =
+            StaticFileTargets { headers: None, receipts: None, transactions: Some(0..=3) };
+        let estimate = static_file_producer.estimate(&targets).expect("estimate");
+        let transactions_estimate = estimate[&StaticFileSegment::Transactions];
+        let transactions = provider_factory
+            .provider()
+            .unwrap()
+            .tx_ref()
+            .entries::<tables::Transactions>()
+            .unwrap();
+        assert_eq!(
+            (transactions_estimate.blocks, transactions_estimate.rows),
+            (4, transactions as u64)
+        );
+
+        // Nothing is written
+        assert_eq!(static_file_provider.get_highest_static_files().transactions, None);
+
+        assert_matches!(static_file_producer.run(targets), Ok(_));
+
+        let data_path = static_file_provider
+            .directory()
+            .join(StaticFileSegment::Transactions.filename(&find_fixed_range(0)));
+        let actual_size = std::fs::metadata(&data_path).unwrap().len() +
+            std::fs::metadata(data_path.with_extension("off")).unwrap().len();
+        assert!(transactions_estimate.bytes <= actual_size);
+        assert!(transactions_estimate.bytes >= actual_size * 9 / 10);
+    }
+
+    #[test]
+    fn estimate_without_database_headers() {
+        let blocks = random_block_range(&mut generators::rng(), 0..=3, B256::ZERO, 2..3);
+        let targets =
+            StaticFileTargets { headers: Some(0..=3), receipts: None, transactions: None };
+
+        // Headers are only in static files
+        let static_db = TestStageDB::default();
+        static_db.insert_blocks(blocks.iter(), StorageKind::Static).expect("insert blocks");
+        assert_eq!(
+            static_db.factory.db_ref().tx().unwrap().entries::<tables::Headers>().unwrap(),
+            0
+        );
+        let static_estimate = StaticFileProducerInner::new(
+            static_db.factory.clone(),
+            static_db.factory.static_file_provider(),
+            PruneModes::default(),
+        )
+        .estimate(&targets)
+        .expect("estimate");
+
+        // Headers are only in the database
+        let database_db = TestStageDB::default();
+        let tx = database_db.factory.db_ref().tx_mut().expect("init tx");
+        for block in &blocks {
+            TestStageDB::insert_header(None, &tx, &block.header, U256::ZERO)
+                .expect("insert block header");
+        }
+        tx.commit().expect("commit tx");
+        let database_estimate = StaticFileProducerInner::new(
+            database_db.factory.clone(),
+            database_db.factory.static_file_provider(),
+            PruneModes::default(),
+        )
+        .estimate(&targets)
+        .expect("estimate");
+
+        // Static files are sampled the same way as the database
+        let headers_estimate = static_estimate[&StaticFileSegment::Headers];
+        assert_eq!((headers_estimate.blocks, headers_estimate.rows), (4, 4));
+        assert_eq!(headers_estimate, database_estimate[&StaticFileSegment::Headers]);
+    }
+
+    #[test]
+    fn dry_run() {
+        let (provider_factory, static_file_provider, _temp_static_files_dir) = setup();
+
+        let mut static_file_producer = StaticFileProducerInner::new(
+            provider_factory,
+            static_file_provider.clone(),
+            PruneModes::default(),
+        );
+        let mut events = static_file_producer.events().into_inner();
+
+        let targets = StaticFileTargets {
+            headers: Some(0..=3),
+            receipts: Some(0..=3),
+            transactions: Some(0..=3),
+        };
+        let estimate = static_file_producer.dry_run(targets.clone()).expect("dry run");
+        assert_eq!(estimate.len(), 3);
+        assert_eq!(
+            events.try_recv(),
+            Ok(StaticFileProducerEvent::DryRun { targets, estimate: estimate.clone() })
+        );
+
+        // Nothing is written
+        assert_eq!(
+            static_file_provider.get_highest_static_files(),
+            HighestStaticFiles { headers: None, receipts: None, transactions: None }
+        );
+        assert!(!static_file_provider
+            .directory()
+            .join(StaticFileSegment::Transactions.filename(&find_fixed_range(0)))
+            .exists());
+
+        assert_matches!(
+            static_file_producer.dry_run(StaticFileTargets {
+                headers: Some(0..=4),
+                receipts: None,
+                transactions: None
+            }),
+            Err(RethError::Provider(ProviderError::HeaderNotFound(_)))
+        );
+        assert_matches!(
+            static_file_producer.dry_run(StaticFileTargets {
+                headers: None,
+                receipts: None,
+                transactions: Some(0..=4)
+            }),
+            Err(RethError::Provider(ProviderError::BlockBodyIndicesNotFound(4)))
+        );
+        assert_matches!(
+            static_file_producer.dry_run(StaticFileTargets {
+                headers: None,
+                receipts: Some(1..=3),
+                transactions: None
+            }),
+            Err(RethError::Provider(ProviderError::UnexpectedStaticFileBlockNumber(
+                StaticFileSegment::Receipts,
+                1,
+                0
+            )))
+        );
+
+        // The static files directory is writable, and the probe file is removed
+        let probe_path = static_file_provider.directory().join(WRITE_PROBE_FILE_NAME);
+        assert!(!probe_path.exists());
+
+        // Files that can't be created in the static files directory fail the dry run
+        reth_primitives::fs::create_dir_all(&probe_path).unwrap();
+        assert_matches!(
+            static_file_producer.dry_run(StaticFileTargets {
+                headers: Some(0..=3),
+                receipts: None,
+                transactions: None
+            }),
+            Err(RethError::Custom(_))
+        );
+    }
+
+    #[test]
+    fn run_segment_finished() {
+        let (provider_factory, static_file_provider, _temp_static_files_dir) = setup();
+
+        let mut static_file_producer = StaticFileProducerInner::new(
+            provider_factory,
+            static_file_provider,
+            PruneModes::default(),
+        );
+        let mut events = static_file_producer.events().into_inner();
+
+        let targets =
+            StaticFileTargets { headers: Some(0..=3), receipts: None, transactions: None };
+        assert_matches!(static_file_producer.run(targets), Ok(_));
+
+        assert_matches!(events.try_recv(), Ok(StaticFileProducerEvent::Started { .. }));
+        let Ok(StaticFileProducerEvent::SegmentFinished { segment, block_range, files }) =
+            events.try_recv()
+        else {
+            panic!("expected segment finished event")
+        };
+        assert_eq!(segment, StaticFileSegment::Headers);
+        assert_eq!(block_range, 0..=3);
+        assert_eq!(files.len(), 3);
+        assert!(files.iter().all(|file| file.exists()));
+    }
+
+    #[test]
+    fn run_with_consistent_reads() {
+        let (provider_factory, static_file_provider, _temp_static_files_dir) = setup();
+
+        let mut static_file_producer = StaticFileProducerInner::new(
+            provider_factory,
+            static_file_provider.clone(),
+            PruneModes::default(),
+        );
+        static_file_producer.consistent_reads = true;
+        let mut events = static_file_producer.events().into_inner();
+
+        // Targets can't go past the highest block visible to the read transaction
+        assert_matches!(
+            static_file_producer.run(StaticFileTargets {
+                headers: Some(0..=4),
+                receipts: None,
+                transactions: None
+            }),
+            Err(RethError::Provider(ProviderError::HeaderNotFound(_)))
+        );
+
+        let targets =
+            StaticFileTargets { headers: Some(0..=3), receipts: Some(0..=3), transactions: None };
+        assert_eq!(static_file_producer.run(targets.clone()), Ok(targets.clone()));
+        assert_eq!(
+            events.try_recv(),
+            Ok(StaticFileProducerEvent::Started { targets, tip: Some(3) })
+        );
+        assert_eq!(
+            static_file_provider.get_highest_static_files(),
+            HighestStaticFiles { headers: Some(3), receipts: Some(3), transactions: None }
+        );
+    }
+
+    #[test]
+    fn run_continue_on_error() {
+        let (provider_factory, static_file_provider, _temp_static_files_dir) = setup();
+
+        let mut static_file_producer = StaticFileProducerInner::new(
+            provider_factory,
+            static_file_provider.clone(),
+            PruneModes::default(),
+        );
+        static_file_producer.continue_on_error = true;
+        let mut events = static_file_producer.events().into_inner();
+
+        // Block 4 doesn't exist, so the receipts segment fails
+        let targets =
+            StaticFileTargets { headers: Some(0..=3), receipts: Some(0..=4), transactions: None };
+        let moved_targets =
+            StaticFileTargets { headers: Some(0..=3), receipts: None, transactions: None };
+        assert_eq!(static_file_producer.run(targets), Ok(moved_targets.clone()));
+        assert_eq!(
+            static_file_provider.get_highest_static_files(),
+            HighestStaticFiles { headers: Some(3), receipts: None, transactions: None }
+        );
+
+        assert_matches!(events.try_recv(), Ok(StaticFileProducerEvent::Started { .. }));
+        assert_eq!(
+            events.try_recv(),
+            Ok(StaticFileProducerEvent::SegmentError {
+                segment: StaticFileSegment::Receipts,
+                error: ProviderError::BlockBodyIndicesNotFound(4).into(),
+            })
+        );
+        assert_matches!(
+            events.try_recv(),
+            Ok(StaticFileProducerEvent::SegmentFinished {
+                segment: StaticFileSegment::Headers,
+                ..
+            })
+        );
+        assert_matches!(
+            events.try_recv(),
+            Ok(StaticFileProducerEvent::Finished { targets, .. }) if targets == moved_targets
+        );
+
+        // Receipts appended before the failure are discarded, so committing the static file
+        // provider doesn't persist them and the segment can be run again
+        static_file_provider.commit().expect("commit static files");
+        assert_eq!(
+            static_file_provider.get_highest_static_files(),
+            HighestStaticFiles { headers: Some(3), receipts: None, transactions: None }
+        );
+
+        let targets =
+            StaticFileTargets { headers: None, receipts: Some(0..=3), transactions: None };
+        assert_eq!(static_file_producer.run(targets.clone()), Ok(targets));
+        assert_eq!(
+            static_file_provider.get_highest_static_files(),
+            HighestStaticFiles { headers: Some(3), receipts: Some(3), transactions: None }
+        );
+    }
+
+    #[test]
+    fn run_retry_after_error() {
+        let (provider_factory, static_file_provider, _temp_static_files_dir) = setup();
+
+        let mut static_file_producer = StaticFileProducerInner::new(
+            provider_factory,
+            static_file_provider.clone(),
+            PruneModes::default(),
+        );
+        let mut events = static_file_producer.events().into_inner();
+
+        // Block 4 doesn't exist, so transactions fail while headers are moved successfully
+        let targets =
+            StaticFileTargets { headers: Some(0..=3), receipts: None, transactions: Some(0..=4) };
+        assert_matches!(
+            static_file_producer.run(targets),
+            Err(RethError::Provider(ProviderError::BlockBodyIndicesNotFound(4)))
+        );
+
+        assert_matches!(events.try_recv(), Ok(StaticFileProducerEvent::Started { .. }));
+        assert_matches!(
+            events.try_recv(),
+            Ok(StaticFileProducerEvent::Failed { segment: StaticFileSegment::Transactions, .. })
+        );
+        assert!(events.try_recv().is_err());
+
+        // Nothing is committed for any segment, even by a later commit
+        static_file_provider.commit().expect("commit static files");
+        assert_eq!(
+            static_file_provider.get_highest_static_files(),
+            HighestStaticFiles { headers: None, receipts: None, transactions: None }
+        );
+
+        // The run can be retried from the highest static files
+        let targets =
+            StaticFileTargets { headers: Some(0..=3), receipts: None, transactions: Some(0..=3) };
+        assert_eq!(static_file_producer.run(targets.clone()), Ok(targets));
+        assert_eq!(
+            static_file_provider.get_highest_static_files(),
+            HighestStaticFiles { headers: Some(3), receipts: None, transactions: Some(3) }
+        );
+    }
+
+    #[test]
+    fn run_single_segment() {
+        let (provider_factory, static_file_provider, _temp_static_files_dir) = setup();
+
+        let mut static_file_producer = StaticFileProducerInner::new(
+            provider_factory,
+            static_file_provider.clone(),
+            PruneModes::default(),
+        );
+
+        let targets = StaticFileTargets::default()
+            .with_segment(StaticFileSegment::Transactions, 0..=2)
+            .expect("set transactions target");
+        assert_eq!(
+            targets,
+            StaticFileTargets { headers: None, receipts: None, transactions: Some(0..=2) }
+        );
+        assert_eq!(static_file_producer.run(targets.clone()), Ok(targets));
+        assert_eq!(
+            static_file_provider.get_highest_static_files(),
+            HighestStaticFiles { headers: None, receipts: None, transactions: Some(2) }
+        );
+
+        // Targets of segments without static files must start at genesis
+        for block_range in [2..=3, 500_000..=500_001] {
+            let block_start = *block_range.start();
+            let targets = StaticFileTargets::default()
+                .with_segment(StaticFileSegment::Headers, block_range)
+                .expect("set headers target");
+            assert_matches!(
+                static_file_producer.dry_run(targets),
+                Err(RethError::Provider(ProviderError::UnexpectedStaticFileBlockNumber(
+                    StaticFileSegment::Headers,
+                    start,
+                    0
+                ))) if start == block_start
+            );
+        }
+
+        // Empty block ranges are rejected
+        #[allow(clippy::reversed_empty_ranges)]
+        let block_range = 5..=3;
+        assert_matches!(
+            StaticFileTargets::default().with_segment(StaticFileSegment::Headers, block_range),
+            Err(RethError::Custom(_))
+        );
+    }
+
+    #[test]
+    fn run_with_max_parallel_segments() {
+        let (provider_factory, static_file_provider, _temp_static_files_dir) = setup();
+        let parallel_dir = tempfile::tempdir().unwrap();
+        let serial_dir = tempfile::tempdir().unwrap();
+
+        // Zero segments can't be moved in parallel
+        assert_matches!(
+            StaticFileProducer::new(
+                provider_factory.clone(),
+                static_file_provider.clone(),
+                PruneModes::default()
+            )
+            .with_max_parallel_segments(0)
+            .err(),
+            Some(RethError::Custom(_))
+        );
+
+        let mut static_file_producer = StaticFileProducerInner::new(
+            provider_factory,
+            static_file_provider,
+            PruneModes::default(),
+        );
+
+        let targets = StaticFileTargets {
+            headers: Some(0..=3),
+            receipts: Some(0..=3),
+            transactions: Some(0..=3),
+        };
+        assert_eq!(
+            static_file_producer.run_to_directory(targets.clone(), parallel_dir.path()),
+            Ok(targets.clone())
+        );
+
+        // Segments are moved one by one
+        static_file_producer.thread_pool = Some(segments_thread_pool(1).unwrap());
+        assert_eq!(
+            static_file_producer.run_to_directory(targets.clone(), serial_dir.path()),
+            Ok(targets)
+        );
+
+        // Static files are identical to the ones moved in parallel
+        let mut files = 0;
+        for entry in reth_primitives::fs::read_dir(parallel_dir.path()).unwrap() {
+            let file_name = entry.unwrap().file_name();
+            assert_eq!(
+                reth_primitives::fs::read(parallel_dir.path().join(&file_name)).unwrap(),
+                reth_primitives::fs::read(serial_dir.path().join(&file_name)).unwrap(),
+            );
+            files += 1;
+        }
+        assert_eq!(files, reth_primitives::fs::read_dir(serial_dir.path()).unwrap().count());
+    }
+
+    #[test]
+    fn run_to_directory_window() {
+        let (provider_factory, static_file_provider, _temp_static_files_dir) = setup();
+        let output_dir = tempfile::tempdir().unwrap();
+
+        // Insert blocks at the beginning of the second static file range
+        let block_start = find_fixed_range(0).end() + 1;
+        let mut rng = generators::rng();
+        let mut headers = Vec::new();
+        let mut tx_ranges = Vec::new();
+        for number in block_start..=block_start + 2 {
+            let header = random_block(&mut rng, number, None, Some(0), Some(0)).header;
+            let tx = provider_factory.db_ref().tx_mut().expect("init tx");
+            TestStageDB::insert_header(None, &tx, &header, U256::ZERO)
+                .expect("insert block header");
+            tx.commit().expect("commit tx");
+            headers.push(header);
+
+            tx_ranges.push(insert_block_body(&provider_factory, number));
+        }
+        let tx_range = tx_ranges[0].start..tx_ranges[2].end;
+
+        let mut static_file_producer = StaticFileProducerInner::new(
+            provider_factory.clone(),
+            static_file_provider.clone(),
+            PruneModes::default(),
+        );
+
+        let targets = StaticFileTargets::default()
+            .with_segment(StaticFileSegment::Headers, block_start..=block_start + 2)
+            .and_then(|targets| {
+                targets.with_segment(StaticFileSegment::Transactions, block_start..=block_start + 2)
+            })
+            .expect("set targets");
+        assert_eq!(
+            static_file_producer.run_to_directory(targets.clone(), output_dir.path()),
+            Ok(targets)
+        );
+
+        // Nothing is written to the configured static files directory
+        assert_eq!(
+            static_file_provider.get_highest_static_files(),
+            HighestStaticFiles { headers: None, receipts: None, transactions: None }
+        );
+
+        let output_provider = StaticFileProvider::new(output_dir.path()).unwrap();
+        assert_eq!(
+            output_provider.get_highest_static_files(),
+            HighestStaticFiles {
+                headers: Some(block_start + 2),
+                receipts: None,
+                transactions: Some(block_start + 2)
+            }
+        );
+        for header in &headers {
+            assert_eq!(
+                output_provider.header_by_number(header.number).unwrap(),
+                Some(header.header().clone())
+            );
+        }
+
+        let db_transactions = provider_factory
+            .db_ref()
+            .tx()
+            .expect("init tx")
+            .cursor_read::<tables::Transactions>()
+            .expect("init cursor")
+            .walk_range(tx_range.clone())
+            .expect("walk transactions")
+            .map(|entry| entry.map(|(_, transaction)| transaction))
+            .collect::<Result<Vec<_>, _>>()
+            .expect("read transactions");
+        assert_eq!(output_provider.transactions_by_tx_range(tx_range), Ok(db_transactions));
+    }
+
+    #[test]
+    fn run_to_directory() {
+        let (provider_factory, _, _temp_static_files_dir) = setup();
+        let static_files_dir = tempfile::tempdir().unwrap();
+        let output_dir = tempfile::tempdir().unwrap();
+
+        let static_file_provider = StaticFileProvider::new(static_files_dir.path())
+            .unwrap()
+            .with_compression(StaticFileSegment::Headers, StaticFileCompression::Uncompressed);
+        let mut static_file_producer = StaticFileProducerInner::new(
+            provider_factory,
+            static_file_provider.clone(),
+            PruneModes::default(),
+        );
+
+        let targets =
+            StaticFileTargets { headers: Some(0..=3), receipts: None, transactions: Some(0..=3) };
+        let result = static_file_producer.run_to_directory(targets.clone(), output_dir.path());
+        assert_eq!(result, Ok(targets));
+
+        // Nothing is written to the configured static files directory
+        assert_eq!(
+            static_file_provider.get_highest_static_files(),
+            HighestStaticFiles { headers: None, receipts: None, transactions: None }
+        );
+
+        let output_provider = StaticFileProvider::new(output_dir.path()).unwrap();
+        assert_eq!(
+            output_provider.get_highest_static_files(),
+            HighestStaticFiles { headers: Some(3), receipts: None, transactions: Some(3) }
+        );
+        assert!(output_provider.header_by_number(3).unwrap().is_some());
+
+        // Static files in the output directory are created with the configured compression
+        let jar = NippyJar::<SegmentHeader>::load(
+            &output_dir.path().join(StaticFileSegment::Headers.filename(&find_fixed_range(0))),
+        )
+        .unwrap();
+        assert!(jar.compressor().is_none());
+
+        // The configured static file provider is restored even if the run panics
+        if cfg!(debug_assertions) {
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                static_file_producer.run_to_directory(
+                    StaticFileTargets { headers: None, receipts: Some(2..=3), transactions: None },
+                    output_dir.path(),
+                )
+            }));
+            assert!(result.is_err());
+        }
+        assert_eq!(static_file_producer.static_file_provider.directory(), static_files_dir.path());
+
+        // Output path must be a directory
+        let output_file = output_dir.path().join("file");
+        reth_primitives::fs::write(&output_file, []).unwrap();
+        assert_matches!(
+            static_file_producer.run_to_directory(
+                StaticFileTargets { headers: Some(0..=3), receipts: None, transactions: None },
+                &output_file,
+            ),
+            Err(RethError::Custom(_))
+        );
+    }
+
+    #[test]
+    fn run_with_manifests() {
+        let (provider_factory, static_file_provider, _temp_static_files_dir) = setup();
+
+        let mut static_file_producer = StaticFileProducerInner::new(
+            provider_factory,
+            static_file_provider.clone(),
+            PruneModes::default(),
+        );
+        static_file_producer.write_manifests = true;
+        let mut events = static_file_producer.events().into_inner();
+
+        let targets =
+            StaticFileTargets { headers: Some(0..=3), receipts: None, transactions: None };
+        assert_matches!(static_file_producer.run(targets), Ok(_));
+
+        assert_matches!(events.try_recv(), Ok(StaticFileProducerEvent::Started { .. }));
+        let Ok(StaticFileProducerEvent::SegmentFinished { files, .. }) = events.try_recv() else {
+            panic!("expected segment finished event")
+        };
+        assert_matches!(events.try_recv(), Ok(StaticFileProducerEvent::Finished { .. }));
+
+        // The static file isn't finalized, so no manifest is written for it
+        let data_path = static_file_provider
+            .directory()
+            .join(StaticFileSegment::Headers.filename(&find_fixed_range(0)));
+        let manifest_path = StaticFileManifest::path(&data_path);
+        assert_eq!(files.len(), 3);
+        assert!(!manifest_path.exists());
+
+        let jar = NippyJar::<SegmentHeader>::load(&data_path).unwrap();
+        StaticFileManifest::new(&jar).unwrap().write(&manifest_path).unwrap();
+        let manifest = StaticFileManifest::load(&manifest_path).unwrap();
+        assert_eq!(manifest.segment, StaticFileSegment::Headers);
+        assert_eq!(manifest.block_range, 0..=3);
+        assert_eq!(manifest.files.len(), 3);
+        assert_eq!(static_file_producer.verify_manifest(&manifest_path), Ok(true));
+
+        // Alter the data file
+        let mut data = reth_primitives::fs::read(&data_path).unwrap();
+        data[0] ^= 1;
+        reth_primitives::fs::write(&data_path, data).unwrap();
+
+        assert_eq!(static_file_producer.verify_manifest(&manifest_path), Ok(false));
+        assert_matches!(
+            events.try_recv(),
+            Ok(StaticFileProducerEvent::VerificationFailed {
+                segment: StaticFileSegment::Headers,
+                reason: StaticFileVerificationFailure::HashMismatch { got: Some(_), .. },
+                ..
+            })
+        );
+        assert_matches!(events.try_recv(), Err(_));
+
+        // Manifests that don't list all required files are rejected
+        for files in [0..0, 0..1, 1..3] {
+            let mut manifest = manifest.clone();
+            manifest.files = manifest.files[files].to_vec();
+            manifest.write(&manifest_path).unwrap();
+            assert_matches!(
+                static_file_producer.verify_manifest(&manifest_path),
+                Err(RethError::Custom(_))
+            );
+        }
+
+        // Files outside of the directory of the manifest are rejected
+        for name in ["../file", "directory/file", "..", ""] {
+            let mut manifest = manifest.clone();
+            let mut file = manifest.files[0].clone();
+            file.name = name.to_string();
+            manifest.files.push(file);
+            manifest.write(&manifest_path).unwrap();
+            assert_matches!(
+                static_file_producer.verify_manifest(&manifest_path),
+                Err(RethError::Custom(_))
+            );
+        }
+    }
+
+    #[test]
+    fn run_with_manifests_for_finalized_static_files() {
+        let (provider_factory, static_file_provider, _temp_static_files_dir) = setup();
+
+        // Fill the receipts static file up to the last blocks of its fixed range. Blocks without
+        // transactions don't add any rows, so the static file stays empty.
+        let fixed_range = find_fixed_range(0);
+        let last_blocks = fixed_range.end() - 3..=fixed_range.end();
+        let mut writer = static_file_provider
+            .latest_writer(StaticFileSegment::Receipts)
+            .expect("get static file writer for receipts");
+        for block in 0..*last_blocks.start() {
+            writer.increment_block(StaticFileSegment::Receipts, block).unwrap();
+        }
+        writer.commit().unwrap();
+        drop(writer);
+
+        let tx = provider_factory.db_ref().tx_mut().expect("init tx");
+        for block in last_blocks.clone() {
+            tx.put::<tables::BlockBodyIndices>(
+                block,
+                StoredBlockBodyIndices { first_tx_num: 0, tx_count: 0 },
+            )
+            .unwrap();
+        }
+        tx.commit().expect("commit tx");
+
+        let mut static_file_producer = StaticFileProducerInner::new(
+            provider_factory,
+            static_file_provider.clone(),
+            PruneModes::default(),
+        );
+        static_file_producer.write_manifests = true;
+        let mut events = static_file_producer.events().into_inner();
+
+        // Moving the last blocks finalizes the static file, so its manifest is written
+        let targets =
+            StaticFileTargets { headers: None, receipts: Some(last_blocks), transactions: None };
+        assert_eq!(static_file_producer.run(targets.clone()), Ok(targets));
+
+        let data_path = static_file_provider
+            .directory()
+            .join(StaticFileSegment::Receipts.filename(&fixed_range));
+        let manifest_path = StaticFileManifest::path(&data_path);
+
+        assert_matches!(events.try_recv(), Ok(StaticFileProducerEvent::Started { .. }));
+        assert_matches!(
+            events.try_recv(),
+            Ok(StaticFileProducerEvent::SegmentFinished { files, .. })
+                if files.len() == 4 && files.contains(&manifest_path)
+        );
+        assert_matches!(events.try_recv(), Ok(StaticFileProducerEvent::Finished { .. }));
+
+        let manifest = StaticFileManifest::load(&manifest_path).unwrap();
+        assert_eq!(manifest.segment, StaticFileSegment::Receipts);
+        assert_eq!(manifest.block_range, fixed_range.start()..=fixed_range.end());
+        assert_eq!(manifest.files.len(), 3);
+        assert_eq!(static_file_producer.verify_manifest(&manifest_path), Ok(true));
+    }
+
     /// Tests that a cloneable [`StaticFileProducer`] type is not susceptible to any race condition.
     #[test]
     fn only_one() {
@@ -42,8 +42,12 @@ pub use writer::NippyJarWriter;
 const NIPPY_JAR_VERSION: usize = 1;
 
 const INDEX_FILE_EXTENSION: &str = "idx";
-const OFFSETS_FILE_EXTENSION: &str = "off";
-const CONFIG_FILE_EXTENSION: &str = "conf";
+/// Extension of the offsets file, appended to the path of the data file.
+pub const OFFSETS_FILE_EXTENSION: &str = "off";
+/// Extension of the configuration file, appended to the path of the data file.
+pub const CONFIG_FILE_EXTENSION: &str = "conf";
+/// Extension appended to the path of a file while it's being written. See [`write_atomically`].
+const TEMP_FILE_EXTENSION: &str = "tmp";
 
 /// A [`RefRow`] is a list of column value slices pointing to either an internal buffer or a
 /// memory-mapped file.
@@ -178,6 +182,15 @@ impl<H: NippyJarHeader> NippyJar<H> {
         self
     }
 
+    /// Adds [`compression::Zstd`] compression without dictionaries, using the given compression
+    /// level.
+    pub fn with_zstd_level(mut self, level: i32) -> Self {
+        self.compressor = Some(Compressors::Zstd(
+            compression::Zstd::new(false, 0, self.columns).with_level(level),
+        ));
+        self
+    }
+
     /// Adds [`compression::Lz4`] compression.
     pub fn with_lz4(mut self) -> Self {
         self.compressor = Some(Compressors::Lz4(compression::Lz4::default()));
@@ -286,6 +299,8 @@ impl<H: NippyJarHeader> NippyJar<H> {
     pub fn delete(self) -> Result<(), NippyJarError> {
         // TODO(joshie): ensure consistency on unexpected shutdown
 
+        self.remove_temp_files()?;
+
         for path in
             [self.data_path().into(), self.index_path(), self.offsets_path(), self.config_path()]
         {
@@ -297,6 +312,18 @@ impl<H: NippyJarHeader> NippyJar<H> {
         Ok(())
     }
 
+    /// Removes temporary files left behind by an interrupted [`write_atomically`].
+    fn remove_temp_files(&self) -> Result<(), NippyJarError> {
+        for path in [self.index_path(), self.config_path()] {
+            let temp_path = temp_path(&path);
+            if temp_path.exists() {
+                reth_primitives::fs::remove_file(temp_path)?;
+            }
+        }
+
+        Ok(())
+    }
+
     /// Returns a [`DataReader`] of the data and offset file
     pub fn open_data_reader(&self) -> Result<DataReader, NippyJarError> {
         DataReader::new(self.data_path())
@@ -395,12 +422,12 @@ impl<H: NippyJarHeader> NippyJar<H> {
     fn freeze_filters(&mut self) -> Result<(), NippyJarError> {
         debug!(target: "nippy-jar", path=?self.index_path(), "Writing offsets and offsets index to file.");
 
-        let mut file = File::create(self.index_path())?;
-        self.offsets_index.serialize_into(&mut file)?;
-        bincode::serialize_into(&mut file, &self.phf)?;
-        bincode::serialize_into(&mut file, &self.filter)?;
-
-        Ok(())
+        write_atomically(&self.index_path(), |file| {
+            self.offsets_index.serialize_into(&mut *file)?;
+            bincode::serialize_into(&mut *file, &self.phf)?;
+            bincode::serialize_into(file, &self.filter)?;
+            Ok(())
+        })
     }
 
     /// Safety checks before creating and returning a [`File`] handle to write data to.
@@ -428,8 +455,61 @@ impl<H: NippyJarHeader> NippyJar<H> {
 
     /// Writes all necessary configuration to file.
     fn freeze_config(&mut self) -> Result<(), NippyJarError> {
-        Ok(bincode::serialize_into(File::create(self.config_path())?, &self)?)
+        write_atomically(&self.config_path(), |file| Ok(bincode::serialize_into(file, &self)?))
+    }
+}
+
+/// Returns the path that a file is written to before being renamed to `path`.
+fn temp_path(path: &Path) -> PathBuf {
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".");
+    temp_path.push(TEMP_FILE_EXTENSION);
+    temp_path.into()
+}
+
+/// Writes a file to a temporary path and renames it to `path` once it's fully written and synced,
+/// so that an interrupted write never leaves a partially written file at `path`.
+///
+/// Only used for the configuration and index files, which are rewritten as a whole. The data and
+/// offsets files are appended to in place instead, and rely on [`NippyJarWriter`] healing them
+/// against the configuration when opened.
+///
+/// If `write` fails, the temporary file is removed. A temporary file left behind by a crash is
+/// removed the next time a [`NippyJarWriter`] is opened.
+fn write_atomically(
+    path: &Path,
+    write: impl FnOnce(&mut File) -> Result<(), NippyJarError>,
+) -> Result<(), NippyJarError> {
+    let temp_path = temp_path(path);
+
+    let mut file = File::create(&temp_path)?;
+    if let Err(err) = write(&mut file).and_then(|_| Ok(file.sync_all()?)) {
+        drop(file);
+        reth_primitives::fs::remove_file(&temp_path)?;
+        return Err(err)
     }
+
+    reth_primitives::fs::rename(&temp_path, path)?;
+    sync_parent_dir(path)?;
+
+    Ok(())
+}
+
+/// Syncs the directory containing `path`, so that a preceding rename survives a crash.
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) -> Result<(), NippyJarError> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    File::open(parent)?.sync_all()?;
+    Ok(())
+}
+
+/// Directories can't be opened as files on this platform, so renames are left to the filesystem.
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) -> Result<(), NippyJarError> {
+    Ok(())
 }
 
 impl<H: NippyJarHeader> InclusionFilter for NippyJar<H> {
@@ -551,7 +631,7 @@ impl DataReader {
 mod tests {
     use super::*;
     use rand::{rngs::SmallRng, seq::SliceRandom, RngCore, SeedableRng};
-    use std::{collections::HashSet, fs::OpenOptions};
+    use std::{collections::HashSet, fs::OpenOptions, io::Write};
 
     type ColumnResults<T> = Vec<ColumnResult<T>>;
     type ColumnValues = Vec<Vec<u8>>;
@@ -1064,6 +1144,58 @@ mod tests {
         test_append_consistency_partial_commit(file_path.path(), &col1, &col2);
     }
 
+    #[test]
+    fn test_interrupted_config_write() {
+        let (col1, col2) = test_data(None);
+        let num_columns = 2;
+        let file_path = tempfile::NamedTempFile::new().unwrap();
+
+        append_two_rows(num_columns, file_path.path(), &col1, &col2);
+
+        // No temporary configuration file is left after a commit
+        let mut nippy = NippyJar::load_without_header(file_path.path()).unwrap();
+        let config_path = nippy.config_path();
+        let temp_config_path = temp_path(&config_path);
+        assert!(!temp_config_path.exists());
+
+        // A failed write removes its temporary file and leaves the committed configuration intact
+        nippy.rows = 3;
+        let result = write_atomically(&config_path, |file| {
+            let config = bincode::serialize(&nippy).unwrap();
+            file.write_all(&config[..config.len() / 2])?;
+            Err(NippyJarError::Custom("interrupted".to_string()))
+        });
+        assert!(result.is_err());
+        assert!(!temp_config_path.exists());
+        assert_eq!(NippyJar::load_without_header(file_path.path()).unwrap().rows, 2);
+
+        // Simulate an unexpected shutdown halfway through writing the configuration, which leaves a
+        // partially written temporary file behind
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            write_atomically(&config_path, |file| {
+                let config = bincode::serialize(&nippy).unwrap();
+                file.write_all(&config[..config.len() / 2])?;
+                panic!("unexpected shutdown");
+            })
+        }));
+        assert!(result.is_err());
+        assert!(temp_config_path.exists());
+
+        // The committed configuration is left intact
+        let nippy = NippyJar::load_without_header(file_path.path()).unwrap();
+        assert_eq!(nippy.rows, 2);
+
+        // Opening a writer removes the partially written temporary file
+        let mut writer = NippyJarWriter::new(nippy).unwrap();
+        assert!(!temp_config_path.exists());
+
+        writer.append_column(Some(Ok(&col1[2]))).unwrap();
+        writer.append_column(Some(Ok(&col2[2]))).unwrap();
+        writer.commit().unwrap();
+        assert!(!temp_config_path.exists());
+        assert_eq!(NippyJar::load_without_header(file_path.path()).unwrap().rows, 3);
+    }
+
     #[test]
     fn test_pruner() {
         let (col1, col2) = test_data(None);
@@ -14,6 +14,13 @@ const OFFSET_SIZE_BYTES: u64 = 8;
 /// Table data is written directly to disk, while offsets and configuration need to be flushed by
 /// calling `commit()`.
 ///
+/// ## Crash consistency
+/// Data and offsets files are appended to in place, so an interrupted write can leave them with
+/// rows that were never committed. `commit()` syncs both before atomically replacing the
+/// configuration, which makes the configuration the source of truth: opening a writer on an
+/// existing jar truncates both files back to the last committed row. Readers only look at the
+/// committed rows, so uncommitted data is never visible to them.
+///
 /// ## Offset file layout
 /// The first byte is the size of a single offset in bytes, `m`.
 /// Then, the file contains `n` entries, each with a size of `m`. Each entry represents an offset,
@@ -47,7 +54,9 @@ impl<H: NippyJarHeader> NippyJarWriter<H> {
         let (data_file, offsets_file, is_created) =
             Self::create_or_open_files(jar.data_path(), &jar.offsets_path())?;
 
-        // Makes sure we don't have dangling data and offset files
+        // Makes sure we don't have dangling data and offset files, nor temporary files left by an
+        // interrupted configuration or index write
+        jar.remove_temp_files()?;
         jar.freeze_config()?;
 
         let mut writer = Self {
@@ -1,6 +1,6 @@
 use super::{
-    metrics::StaticFileProviderMetrics, LoadedJar, StaticFileJarProvider, StaticFileProviderRW,
-    StaticFileProviderRWRefMut, BLOCKS_PER_STATIC_FILE,
+    metrics::StaticFileProviderMetrics, LoadedJar, StaticFileCompression, StaticFileJarProvider,
+    StaticFileProviderRW, StaticFileProviderRWRefMut, BLOCKS_PER_STATIC_FILE,
 };
 use crate::{
     to_range, BlockHashReader, BlockNumReader, BlockReader, BlockSource, HeaderProvider,
@@ -50,6 +50,19 @@ impl StaticFileProvider {
         provider.initialize_index()?;
         Ok(provider)
     }
+
+    /// Creates a new [`StaticFileProvider`] at the provided directory, with the same filters,
+    /// compression and metrics configuration as this one.
+    pub fn with_directory(&self, path: impl AsRef<Path>) -> ProviderResult<Self> {
+        let mut provider = StaticFileProviderInner::new(path)?;
+        provider.load_filters = self.load_filters;
+        provider.compression = self.compression.clone();
+        provider.metrics = self.metrics.clone();
+
+        let provider = Self(Arc::new(provider));
+        provider.initialize_index()?;
+        Ok(provider)
+    }
 }
 
 impl Deref for StaticFileProvider {
@@ -77,6 +90,8 @@ pub struct StaticFileProviderInner {
     load_filters: bool,
     /// Maintains a map of StaticFile writers for each [`StaticFileSegment`]
     writers: DashMap<StaticFileSegment, StaticFileProviderRW>,
+    /// Compression overrides for newly created static files of each [`StaticFileSegment`].
+    compression: HashMap<StaticFileSegment, StaticFileCompression>,
     metrics: Option<Arc<StaticFileProviderMetrics>>,
 }
 
@@ -90,6 +105,7 @@ impl StaticFileProviderInner {
             static_files_tx_index: Default::default(),
             path: path.as_ref().to_path_buf(),
             load_filters: false,
+            compression: Default::default(),
             metrics: None,
         };
 
@@ -106,6 +122,28 @@ impl StaticFileProvider {
         Self(Arc::new(provider))
     }
 
+    /// Sets the compression used for newly created static files of a [`StaticFileSegment`].
+    ///
+    /// Static files that already exist keep the compression they were created with.
+    pub fn with_compression(
+        self,
+        segment: StaticFileSegment,
+        compression: StaticFileCompression,
+    ) -> Self {
+        let mut provider =
+            Arc::try_unwrap(self.0).expect("should be called when initializing only");
+        provider.compression.insert(segment, compression);
+        Self(Arc::new(provider))
+    }
+
+    /// Returns the compression used for newly created static files of a [`StaticFileSegment`].
+    pub fn compression(&self, segment: StaticFileSegment) -> StaticFileCompression {
+        self.compression
+            .get(&segment)
+            .copied()
+            .unwrap_or_else(|| StaticFileCompression::default_for_segment(segment))
+    }
+
     /// Enables metrics on the [`StaticFileProvider`].
     pub fn with_metrics(self) -> Self {
         let mut provider =
@@ -717,6 +755,12 @@ pub trait StaticFileWriter {
 
     /// Commits all changes of all [`StaticFileProviderRW`] of all [`StaticFileSegment`].
     fn commit(&self) -> ProviderResult<()>;
+
+    /// Drops the [`StaticFileProviderRW`] of a [`StaticFileSegment`] without committing it.
+    ///
+    /// Any uncommitted changes are discarded, and the next writer of the segment is opened from
+    /// the last committed state on disk.
+    fn discard_writer(&self, segment: StaticFileSegment);
 }
 
 impl StaticFileWriter for StaticFileProvider {
@@ -753,6 +797,10 @@ impl StaticFileWriter for StaticFileProvider {
         }
         Ok(())
     }
+
+    fn discard_writer(&self, segment: StaticFileSegment) {
+        self.writers.remove(&segment);
+    }
 }
 
 impl HeaderProvider for StaticFileProvider {
@@ -16,6 +16,37 @@ use std::{ops::Deref, sync::Arc};
 
 const BLOCKS_PER_STATIC_FILE: u64 = 500_000;
 
+/// Compression applied to the columns of newly created static files.
+///
+/// Static files store their compression in their configuration file, so existing files are always
+/// read back with the compression they were written with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StaticFileCompression {
+    /// No compression.
+    Uncompressed,
+    /// LZ4 compression.
+    Lz4,
+    /// Zstd compression without dictionaries, using the given compression level. A level of `0`
+    /// uses zstd's default.
+    Zstd {
+        /// Compression level.
+        level: i32,
+    },
+}
+
+impl StaticFileCompression {
+    /// Returns the default compression of a [`StaticFileSegment`].
+    ///
+    /// Transaction and Receipt already have the compression scheme used natively in their
+    /// encoding (zstd-dictionary), so only headers are compressed.
+    pub const fn default_for_segment(segment: StaticFileSegment) -> Self {
+        match segment {
+            StaticFileSegment::Headers => Self::Lz4,
+            StaticFileSegment::Transactions | StaticFileSegment::Receipts => Self::Uncompressed,
+        }
+    }
+}
+
 /// Alias type for each specific `NippyJar`.
 type LoadedJarRef<'a> = dashmap::mapref::one::Ref<'a, (u64, StaticFileSegment), LoadedJar>;
 
@@ -66,6 +97,7 @@ mod tests {
         CanonicalHeaders, HeaderNumbers, HeaderTerminalDifficulties, Headers, RawTable,
     };
     use reth_interfaces::test_utils::generators::{self, random_header_range};
+    use reth_nippy_jar::compression::Compressors;
     use reth_primitives::{static_file::find_fixed_range, BlockNumber, B256, U256};
 
     #[test]
@@ -176,4 +208,49 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_compression_roundtrip() {
+        let static_files_path = tempfile::tempdir().unwrap();
+        let headers = random_header_range(&mut generators::rng(), 0..100, B256::random());
+
+        for (compression, expected) in [
+            (StaticFileCompression::Uncompressed, None),
+            (StaticFileCompression::Zstd { level: 19 }, Some(true)),
+        ] {
+            let path = static_files_path.path().join(format!("{compression:?}"));
+            let provider = StaticFileProvider::new(&path)
+                .unwrap()
+                .with_compression(StaticFileSegment::Headers, compression);
+
+            {
+                let mut writer = provider.latest_writer(StaticFileSegment::Headers).unwrap();
+                let mut td = U256::ZERO;
+                for header in &headers {
+                    td += header.header().difficulty;
+                    writer.append_header(header.header().clone(), td, header.hash()).unwrap();
+                }
+                writer.commit().unwrap();
+            }
+
+            // Compression is persisted in the static file configuration
+            let jar = NippyJar::<SegmentHeader>::load(
+                &path.join(StaticFileSegment::Headers.filename(&find_fixed_range(0))),
+            )
+            .unwrap();
+            assert_eq!(
+                jar.compressor().map(|compressor| matches!(compressor, Compressors::Zstd(_))),
+                expected
+            );
+
+            // Data is transparently decompressed on read
+            let provider = StaticFileProvider::new(&path).unwrap();
+            for header in &headers {
+                assert_eq!(
+                    provider.header_by_number(header.number).unwrap(),
+                    Some(header.header().clone())
+                );
+            }
+        }
+    }
 }
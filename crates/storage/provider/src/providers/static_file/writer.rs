@@ -1,7 +1,8 @@
 use crate::providers::static_file::metrics::StaticFileProviderOperation;
 
 use super::{
-    manager::StaticFileProviderInner, metrics::StaticFileProviderMetrics, StaticFileProvider,
+    manager::StaticFileProviderInner, metrics::StaticFileProviderMetrics, StaticFileCompression,
+    StaticFileProvider,
 };
 use dashmap::mapref::one::RefMut;
 use reth_codecs::Compact;
@@ -71,7 +72,8 @@ impl StaticFileProviderRW {
             ),
             Err(ProviderError::MissingStaticFileBlock(_, _)) => {
                 let path = static_file_provider.directory().join(segment.filename(&block_range));
-                (create_jar(segment, &path, block_range), path)
+                let compression = static_file_provider.compression(segment);
+                (create_jar(segment, &path, block_range, compression), path)
             }
             Err(err) => return Err(err),
         };
@@ -556,18 +558,17 @@ fn create_jar(
     segment: StaticFileSegment,
     path: &Path,
     expected_block_range: SegmentRangeInclusive,
+    compression: StaticFileCompression,
 ) -> NippyJar<SegmentHeader> {
-    let mut jar = NippyJar::new(
+    let jar = NippyJar::new(
         segment.columns(),
         path,
         SegmentHeader::new(expected_block_range, None, None, segment),
     );
 
-    // Transaction and Receipt already have the compression scheme used natively in its encoding.
-    // (zstd-dictionary)
-    if segment.is_headers() {
-        jar = jar.with_lz4();
+    match compression {
+        StaticFileCompression::Uncompressed => jar,
+        StaticFileCompression::Lz4 => jar.with_lz4(),
+        StaticFileCompression::Zstd { level } => jar.with_zstd_level(level),
     }
-
-    jar
 }